@@ -0,0 +1,204 @@
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
+
+const DEFAULT_SYMBOL: &str = "TNKR";
+const DEFAULT_DECIMALS: u32 = 12;
+
+/// Symbol and decimal places of a token, used to display and parse amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+impl Default for Token {
+    fn default() -> Self {
+        Self {
+            symbol: DEFAULT_SYMBOL.to_string(),
+            decimals: DEFAULT_DECIMALS,
+        }
+    }
+}
+
+impl Token {
+    /// Reads `tokenSymbol` and `tokenDecimals` from the chain's system properties,
+    /// which may hold either a single value or a list with the native token first.
+    pub fn from_properties(properties: &Map<String, Value>) -> Self {
+        let first = |value: &Value| match value {
+            Value::Array(values) => values.first().cloned(),
+            other => Some(other.clone()),
+        };
+
+        let symbol = properties
+            .get("tokenSymbol")
+            .and_then(first)
+            .and_then(|value| value.as_str().map(String::from))
+            .unwrap_or(DEFAULT_SYMBOL.to_string());
+
+        let decimals = properties
+            .get("tokenDecimals")
+            .and_then(first)
+            .and_then(|value| value.as_u64())
+            .map(|decimals| decimals as u32)
+            .unwrap_or(DEFAULT_DECIMALS);
+
+        Self { symbol, decimals }
+    }
+
+    pub fn amount(&self, planck: u128) -> Balance {
+        Balance::new(planck, self.decimals)
+    }
+
+    pub fn parse(&self, amount: &str) -> Option<Balance> {
+        Balance::parse(amount, self.decimals)
+    }
+
+    /// Formats an amount together with the token symbol, e.g. `12.5 TNKR`.
+    pub fn display(&self, planck: u128) -> String {
+        format!("{} {}", self.amount(planck), self.symbol)
+    }
+}
+
+/// An exact token amount in planck, displayed as a decimal number of units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Balance {
+    planck: u128,
+    decimals: u32,
+}
+
+impl Balance {
+    pub fn new(planck: u128, decimals: u32) -> Self {
+        Self { planck, decimals }
+    }
+
+    pub fn planck(&self) -> u128 {
+        self.planck
+    }
+
+    /// Parses a decimal unit amount (e.g. "12.5") without going through floating point,
+    /// rejecting anything with more fractional digits than the token supports.
+    pub fn parse(amount: &str, decimals: u32) -> Option<Self> {
+        let amount = amount.trim();
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+        if (whole.is_empty() && fraction.is_empty())
+            || fraction.len() > decimals as usize
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole.parse::<u128>().ok()?
+        };
+
+        let fraction = if decimals == 0 {
+            0
+        } else {
+            format!("{:0<width$}", fraction, width = decimals as usize)
+                .parse::<u128>()
+                .ok()?
+        };
+
+        let planck = whole
+            .checked_mul(10u128.checked_pow(decimals)?)?
+            .checked_add(fraction)?;
+
+        Some(Self::new(planck, decimals))
+    }
+}
+
+impl fmt::Display for Balance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(one) = 10u128.checked_pow(self.decimals) else {
+            return write!(f, "{}", self.planck);
+        };
+
+        let whole = self.planck / one;
+        let fraction = self.planck % one;
+
+        if fraction == 0 {
+            write!(f, "{}", whole)
+        } else {
+            let fraction = format!("{:0>width$}", fraction, width = self.decimals as usize);
+
+            write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+impl Serialize for Balance {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planck(amount: &str, decimals: u32) -> Option<u128> {
+        Balance::parse(amount, decimals).map(|balance| balance.planck())
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for amount in ["0", "1", "12.5", "0.000000000001", "1000000.123456789012"] {
+            assert_eq!(Balance::parse(amount, 12).unwrap().to_string(), amount);
+        }
+    }
+
+    #[test]
+    fn parse_whole_and_fractional_amounts() {
+        assert_eq!(planck("12.5", 12), Some(12_500_000_000_000));
+        assert_eq!(planck(" 3 ", 12), Some(3_000_000_000_000));
+        assert_eq!(planck("1.", 12), Some(1_000_000_000_000));
+        assert_eq!(planck(".5", 12), Some(500_000_000_000));
+    }
+
+    #[test]
+    fn parse_without_decimals() {
+        assert_eq!(planck("42", 0), Some(42));
+        assert_eq!(planck("42.", 0), Some(42));
+        assert_eq!(planck("42.0", 0), None);
+        assert_eq!(Balance::new(42, 0).to_string(), "42");
+    }
+
+    #[test]
+    fn parse_rejects_more_fractional_digits_than_decimals() {
+        assert_eq!(planck("0.0000000000001", 12), None);
+        assert_eq!(planck("1.234", 2), None);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_amounts() {
+        for amount in ["", " ", ".", "-1", "+1", "1e3", "1,5", "1.2.3", "abc"] {
+            assert_eq!(planck(amount, 12), None, "{:?}", amount);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_u128_overflow() {
+        assert_eq!(
+            planck("340282366920938463463374607431768211455", 0),
+            Some(u128::MAX)
+        );
+        assert_eq!(planck("340282366920938463463374607431768211456", 0), None);
+        assert_eq!(planck("340282366920938463463374607431768211455", 12), None);
+        assert_eq!(planck("340282366920938463463374607.431768211456", 12), None);
+    }
+
+    #[test]
+    fn display_trims_trailing_zeros() {
+        assert_eq!(Balance::new(1_500_000_000_000, 12).to_string(), "1.5");
+        assert_eq!(Balance::new(1_000_000_000_000, 12).to_string(), "1");
+        assert_eq!(Balance::new(10_000_000_000, 12).to_string(), "0.01");
+        assert_eq!(Balance::new(0, 12).to_string(), "0");
+    }
+}
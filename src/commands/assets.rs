@@ -0,0 +1,179 @@
+use crate::{
+    balance::Token,
+    commands::{connect, storage_at, ExtraArgs},
+    error::{ApiError, AssetError, CliError},
+    tinkernet::{
+        self,
+        runtime_types::{
+            orml_traits::asset_registry::AssetMetadata,
+            tinkernet_runtime::assets::CustomAssetMetadata, xcm::VersionedMultiLocation,
+        },
+    },
+};
+use serde::Serialize;
+use subxt::{dynamic::Value, storage::Storage, OnlineClient, PolkadotConfig};
+
+/// A transferable asset: the native token when `id` is `None`, otherwise an
+/// `orml_tokens` currency registered in `AssetRegistry`.
+#[derive(Debug)]
+pub struct Asset {
+    pub id: Option<u32>,
+    pub token: Token,
+    pub existential_deposit: u128,
+    pub location: Option<VersionedMultiLocation>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct AssetRecord {
+    id: u32,
+    symbol: String,
+    name: String,
+    decimals: u32,
+    existential_deposit: String,
+    location: Option<String>,
+}
+
+pub async fn assets_list_command(json: bool, extra: ExtraArgs) -> Result<(), CliError> {
+    let connection = connect(extra.endpoint).await?;
+    let storage = storage_at(&connection, extra.at.as_deref()).await?;
+
+    let records: Vec<AssetRecord> = registered_assets(&storage)
+        .await?
+        .into_iter()
+        .map(|(id, metadata)| {
+            let token = metadata_token(&metadata);
+
+            AssetRecord {
+                id,
+                symbol: token.symbol.clone(),
+                name: String::from_utf8_lossy(&metadata.name).to_string(),
+                decimals: token.decimals,
+                existential_deposit: token.amount(metadata.existential_deposit).to_string(),
+                location: metadata.location.map(|location| format!("{:?}", location)),
+            }
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else {
+        for record in records {
+            println!(
+                "#{:<4} {:<8} decimals: {:<3} ED: {:<12} location: {}",
+                record.id,
+                record.symbol,
+                record.decimals,
+                record.existential_deposit,
+                record.location.unwrap_or(String::from("none"))
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves `--asset` from either a numeric asset id or a symbol (case insensitive),
+/// falling back to the native token when no asset or the native symbol is given.
+pub async fn resolve_asset(
+    api: &OnlineClient<PolkadotConfig>,
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    native: &Token,
+    asset: Option<&str>,
+) -> Result<Asset, CliError> {
+    let native_asset = || -> Result<Asset, CliError> {
+        Ok(Asset {
+            id: None,
+            token: native.clone(),
+            existential_deposit: api
+                .constants()
+                .at(&tinkernet::constants().balances().existential_deposit())
+                .map_err(|error| ApiError::DecodeFailed(error.into()))?,
+            location: None,
+        })
+    };
+
+    let Some(asset) = asset.map(str::trim) else {
+        return native_asset();
+    };
+
+    if asset.eq_ignore_ascii_case(&native.symbol) {
+        return native_asset();
+    }
+
+    let maybe_id = asset.parse::<u32>().ok();
+
+    registered_assets(storage)
+        .await?
+        .into_iter()
+        .find(|(id, metadata)| {
+            Some(*id) == maybe_id || metadata_token(metadata).symbol.eq_ignore_ascii_case(asset)
+        })
+        .map(|(id, metadata)| Asset {
+            id: Some(id),
+            token: metadata_token(&metadata),
+            existential_deposit: metadata.existential_deposit,
+            location: metadata.location,
+        })
+        .ok_or(AssetError::UnknownAsset(asset.to_string()).into())
+}
+
+/// Free balance of `account` in the given asset.
+pub async fn free_balance(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    asset: &Asset,
+    account: subxt::utils::AccountId32,
+) -> Result<u128, CliError> {
+    Ok(match asset.id {
+        None => {
+            storage
+                .fetch_or_default(&tinkernet::storage().system().account(account))
+                .await
+                .map_err(ApiError::StorageFailed)?
+                .data
+                .free
+        }
+        Some(currency_id) => {
+            storage
+                .fetch_or_default(&tinkernet::storage().tokens().accounts(account, currency_id))
+                .await
+                .map_err(ApiError::StorageFailed)?
+                .free
+        }
+    })
+}
+
+async fn registered_assets(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<Vec<(u32, AssetMetadata<u128, CustomAssetMetadata>)>, CliError> {
+    let keys: Vec<Value> = Vec::new();
+    let metadata_query = subxt::dynamic::storage("AssetRegistry", "Metadata", keys);
+
+    let mut results = storage
+        .iter(metadata_query)
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let mut assets = Vec::new();
+
+    while let Some(Ok(kv)) = results.next().await {
+        if let Some(id) = kv.keys[0].as_u128().map(|k| k as u32) {
+            assets.push((
+                id,
+                kv.value
+                    .as_type::<AssetMetadata<u128, CustomAssetMetadata>>()
+                    .map_err(|error| ApiError::DecodeFailed(error.into()))?,
+            ));
+        }
+    }
+
+    assets.sort_by_key(|(id, _)| *id);
+
+    Ok(assets)
+}
+
+fn metadata_token(metadata: &AssetMetadata<u128, CustomAssetMetadata>) -> Token {
+    Token {
+        symbol: String::from_utf8_lossy(&metadata.symbol).to_string(),
+        decimals: metadata.decimals,
+    }
+}
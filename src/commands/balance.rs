@@ -0,0 +1,268 @@
+use crate::{
+    balance::{Balance, Token},
+    commands::{assets::resolve_asset, connect, storage_at, Connection, ExtraArgs},
+    error::{ApiError, BalanceError, CliError},
+    tinkernet,
+};
+use serde::Serialize;
+use subxt::{
+    ext::sp_core::crypto::{AccountId32, Ss58Codec},
+    storage::Storage,
+    OnlineClient, PolkadotConfig,
+};
+
+const VESTING_LOCK_ID: &[u8; 8] = b"ormlvest";
+
+#[derive(Serialize, Debug, Clone)]
+struct NamedAmount {
+    id: String,
+    amount: Balance,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct AccountOverview {
+    account: String,
+    symbol: String,
+    free: Balance,
+    reserved: Balance,
+    frozen: Balance,
+    vesting_locked: Balance,
+    vesting_total: Balance,
+    staked: Balance,
+    unbonding: Balance,
+    transferable: Balance,
+    locks: Vec<NamedAmount>,
+    holds: Vec<NamedAmount>,
+    freezes: Vec<Balance>,
+}
+
+pub async fn balance_command(
+    accounts: Vec<String>,
+    asset: Option<String>,
+    json: bool,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let account_ids = accounts
+        .iter()
+        .map(|account| {
+            AccountId32::from_string(account.trim())
+                .map(|id| {
+                    (
+                        account.trim().to_string(),
+                        subxt::utils::AccountId32::from(id),
+                    )
+                })
+                .map_err(|_| BalanceError::InvalidAddress(account.clone()))
+        })
+        .collect::<Result<Vec<_>, BalanceError>>()?;
+
+    let connection = connect(extra.endpoint).await?;
+    let storage = storage_at(&connection, extra.at.as_deref()).await?;
+    let Connection { api, token, .. } = connection;
+
+    let asset = resolve_asset(&api, &storage, &token, asset.as_deref()).await?;
+
+    let mut overviews: Vec<AccountOverview> = Vec::new();
+
+    for (account, id) in account_ids {
+        overviews.push(match asset.id {
+            None => native_overview(&storage, &asset.token, account, id).await?,
+            Some(currency_id) => {
+                token_overview(&storage, &asset.token, currency_id, account, id).await?
+            }
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&overviews)?);
+    } else {
+        for overview in overviews {
+            print_overview(&overview);
+        }
+    }
+
+    Ok(())
+}
+
+async fn native_overview(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    token: &Token,
+    account: String,
+    id: subxt::utils::AccountId32,
+) -> Result<AccountOverview, CliError> {
+    let data = storage
+        .fetch_or_default(&tinkernet::storage().system().account(id.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .data;
+
+    let locks = storage
+        .fetch_or_default(&tinkernet::storage().balances().locks(id.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .0;
+
+    let holds = storage
+        .fetch_or_default(&tinkernet::storage().balances().holds(id.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .0;
+
+    let freezes = storage
+        .fetch_or_default(&tinkernet::storage().balances().freezes(id.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .0;
+
+    let vesting_schedules = storage
+        .fetch_or_default(&tinkernet::storage().vesting().vesting_schedules(id.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .0;
+
+    let ledger = storage
+        .fetch(&tinkernet::storage().ocif_staking().ledger(id))
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let vesting_locked = locks
+        .iter()
+        .find(|lock| &lock.id == VESTING_LOCK_ID)
+        .map(|lock| lock.amount)
+        .unwrap_or(0);
+
+    let vesting_total: u128 = vesting_schedules
+        .iter()
+        .map(|schedule| {
+            schedule
+                .per_period
+                .saturating_mul(schedule.period_count as u128)
+        })
+        .sum();
+
+    let (locked_in_staking, unbonding) = ledger
+        .map(|ledger| {
+            (
+                ledger.locked,
+                ledger
+                    .unbonding_info
+                    .unlocking_chunks
+                    .iter()
+                    .map(|chunk| chunk.amount)
+                    .sum::<u128>(),
+            )
+        })
+        .unwrap_or((0, 0));
+
+    // Mirrors pallet_balances: reserved funds count towards the frozen amount.
+    let transferable = data
+        .free
+        .saturating_sub(data.frozen.saturating_sub(data.reserved));
+
+    Ok(AccountOverview {
+        account,
+        symbol: token.symbol.clone(),
+        free: token.amount(data.free),
+        reserved: token.amount(data.reserved),
+        frozen: token.amount(data.frozen),
+        vesting_locked: token.amount(vesting_locked),
+        vesting_total: token.amount(vesting_total),
+        staked: token.amount(locked_in_staking.saturating_sub(unbonding)),
+        unbonding: token.amount(unbonding),
+        transferable: token.amount(transferable),
+        locks: locks
+            .iter()
+            .map(|lock| NamedAmount {
+                id: lock_id_to_string(&lock.id),
+                amount: token.amount(lock.amount),
+            })
+            .collect(),
+        holds: holds
+            .iter()
+            .map(|hold| NamedAmount {
+                id: lock_id_to_string(&hold.id),
+                amount: token.amount(hold.amount),
+            })
+            .collect(),
+        freezes: freezes
+            .iter()
+            .map(|freeze| token.amount(freeze.amount))
+            .collect(),
+    })
+}
+
+/// Balances of an `orml_tokens` currency, which has no vesting or staking.
+async fn token_overview(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    token: &Token,
+    currency_id: u32,
+    account: String,
+    id: subxt::utils::AccountId32,
+) -> Result<AccountOverview, CliError> {
+    let data = storage
+        .fetch_or_default(
+            &tinkernet::storage()
+                .tokens()
+                .accounts(id.clone(), currency_id),
+        )
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let locks = storage
+        .fetch_or_default(&tinkernet::storage().tokens().locks(id, currency_id))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .0;
+
+    Ok(AccountOverview {
+        account,
+        symbol: token.symbol.clone(),
+        free: token.amount(data.free),
+        reserved: token.amount(data.reserved),
+        frozen: token.amount(data.frozen),
+        vesting_locked: token.amount(0),
+        vesting_total: token.amount(0),
+        staked: token.amount(0),
+        unbonding: token.amount(0),
+        transferable: token.amount(data.free.saturating_sub(data.frozen)),
+        locks: locks
+            .iter()
+            .map(|lock| NamedAmount {
+                id: lock_id_to_string(&lock.id),
+                amount: token.amount(lock.amount),
+            })
+            .collect(),
+        holds: Vec::new(),
+        freezes: Vec::new(),
+    })
+}
+
+fn lock_id_to_string(id: &[u8; 8]) -> String {
+    String::from_utf8_lossy(id)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string()
+}
+
+fn print_overview(overview: &AccountOverview) {
+    let symbol = &overview.symbol;
+
+    println!("{}", overview.account);
+    println!("  free:            {} {}", overview.free, symbol);
+    println!("  reserved:        {} {}", overview.reserved, symbol);
+    println!("  frozen:          {} {}", overview.frozen, symbol);
+    println!("  vesting locked:  {} {}", overview.vesting_locked, symbol);
+    println!("  staked:          {} {}", overview.staked, symbol);
+    println!("  unbonding:       {} {}", overview.unbonding, symbol);
+    println!("  transferable:    {} {}", overview.transferable, symbol);
+
+    for lock in &overview.locks {
+        println!("  lock {:<10}  {} {}", lock.id, lock.amount, symbol);
+    }
+
+    for hold in &overview.holds {
+        println!("  hold {:<10}  {} {}", hold.id, hold.amount, symbol);
+    }
+
+    println!();
+}
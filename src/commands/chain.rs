@@ -0,0 +1,171 @@
+use crate::{
+    commands::{connect, metadata::metadata_mismatch, resolve_block, Connection, ExtraArgs},
+    error::{ApiError, CliError},
+    staking::current_era,
+    tinkernet,
+};
+use serde::Serialize;
+use subxt::{backend::legacy::LegacyRpcMethods, utils::H256, OnlineClient, PolkadotConfig};
+
+/// Number of blocks the block time is averaged over.
+const BLOCK_TIME_SAMPLE: u32 = 10;
+
+#[derive(Serialize, Debug)]
+struct ChainInfo {
+    chain: String,
+    node: String,
+    spec_version: u32,
+    transaction_version: u32,
+    genesis_hash: String,
+    best_block: u32,
+    finalized_block: u32,
+    peers: usize,
+    syncing: bool,
+    token_symbol: String,
+    token_decimals: u32,
+    current_era: u32,
+    block_time_ms: Option<u64>,
+    metadata_up_to_date: bool,
+}
+
+/// Summarizes the chain and node behind the endpoint, to check it before running anything
+/// against it. The era and block time are read at `--at` when given.
+pub async fn chain_info_command(json: bool, extra: ExtraArgs) -> Result<(), CliError> {
+    let connection = connect(extra.endpoint).await?;
+    let block_hash = resolve_block(&connection, extra.at.as_deref()).await?;
+    let Connection { api, rpc, token } = connection;
+
+    let chain = rpc.system_chain().await.map_err(ApiError::StorageFailed)?;
+    let node = format!(
+        "{} {}",
+        rpc.system_name().await.map_err(ApiError::StorageFailed)?,
+        rpc.system_version()
+            .await
+            .map_err(ApiError::StorageFailed)?
+    );
+    let health = rpc.system_health().await.map_err(ApiError::StorageFailed)?;
+
+    let best_block = block_number(&rpc, None).await?;
+    let finalized_hash = rpc
+        .chain_get_finalized_head()
+        .await
+        .map_err(ApiError::StorageFailed)?;
+    let finalized_block = block_number(&rpc, Some(finalized_hash)).await?;
+
+    let version = api.runtime_version();
+
+    let info = ChainInfo {
+        chain,
+        node,
+        spec_version: version.spec_version,
+        transaction_version: version.transaction_version,
+        genesis_hash: format!("0x{}", hex::encode(api.genesis_hash().as_bytes())),
+        best_block,
+        finalized_block,
+        peers: health.peers,
+        syncing: health.is_syncing,
+        token_symbol: token.symbol.clone(),
+        token_decimals: token.decimals,
+        current_era: current_era(&api.storage().at(block_hash)).await?,
+        block_time_ms: block_time(&api, &rpc, block_hash).await?,
+        metadata_up_to_date: metadata_mismatch(&api).is_none(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        println!("Chain:               {}", info.chain);
+        println!("Node:                {}", info.node);
+        println!(
+            "Runtime:             spec {}, transaction {}",
+            info.spec_version, info.transaction_version
+        );
+        println!("Genesis hash:        {}", info.genesis_hash);
+        println!("Best block:          #{}", info.best_block);
+        println!("Finalized block:     #{}", info.finalized_block);
+        println!(
+            "Peers:               {}{}",
+            info.peers,
+            if info.syncing { " (syncing)" } else { "" }
+        );
+        println!(
+            "Token:               {} ({} decimals)",
+            info.token_symbol, info.token_decimals
+        );
+        println!("Current era:         {}", info.current_era);
+        println!(
+            "Block time:          {}",
+            info.block_time_ms
+                .map(|ms| format!("{:.1}s", ms as f64 / 1000.0))
+                .unwrap_or(String::from("unknown"))
+        );
+        println!(
+            "Metadata:            {}",
+            if info.metadata_up_to_date {
+                "up to date"
+            } else {
+                "outdated, run `metadata update` and rebuild"
+            }
+        );
+    }
+
+    Ok(())
+}
+
+async fn block_number(
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    hash: Option<H256>,
+) -> Result<u32, CliError> {
+    Ok(rpc
+        .chain_get_header(hash)
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .ok_or_else(|| {
+            ApiError::BlockNotFound(
+                hash.map(|hash| format!("0x{}", hex::encode(hash.as_bytes())))
+                    .unwrap_or(String::from("best")),
+            )
+        })?
+        .number)
+}
+
+/// Average time between the last `BLOCK_TIME_SAMPLE` blocks up to `block_hash`, from the
+/// difference of their `Timestamp::Now`.
+async fn block_time(
+    api: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    block_hash: H256,
+) -> Result<Option<u64>, CliError> {
+    let Some(start) = block_number(rpc, Some(block_hash))
+        .await?
+        .checked_sub(BLOCK_TIME_SAMPLE)
+    else {
+        return Ok(None);
+    };
+
+    let Some(start_hash) = rpc
+        .chain_get_block_hash(Some(start.into()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+    else {
+        return Ok(None);
+    };
+
+    let now = api
+        .storage()
+        .at(block_hash)
+        .fetch_or_default(&tinkernet::storage().timestamp().now())
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let then = api
+        .storage()
+        .at(start_hash)
+        .fetch_or_default(&tinkernet::storage().timestamp().now())
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    Ok(Some(
+        now.saturating_sub(then) / u64::from(BLOCK_TIME_SAMPLE),
+    ))
+}
@@ -1,18 +1,17 @@
 use crate::{
-    commands::{consts::TINKERNET_WEBSOCKET, get_signer_interactive, ExtraArgs},
+    commands::{
+        connect, ensure_latest, get_signer_interactive, sign_and_submit, Connection, ExtraArgs,
+    },
     error::{ApiError, ClaimError, CliError},
     keystore::Keystore,
+    staking::{core_rewards, registered_cores, staker_rewards, EraReward},
     tinkernet::{
         self,
         runtime_types::{
-            pallet_ocif_staking::{pallet::Call as OcifStakingCall, primitives::CoreStakeInfo},
-            tinkernet_runtime::RuntimeCall,
+            pallet_ocif_staking::pallet::Call as OcifStakingCall, tinkernet_runtime::RuntimeCall,
         },
     },
-    util::planck_to_unit,
 };
-use inquire::Confirm;
-use subxt::{OnlineClient, PolkadotConfig};
 
 pub enum EraRange {
     All,
@@ -21,6 +20,36 @@ pub enum EraRange {
     EraToEra(u32, u32),
 }
 
+impl EraRange {
+    fn contains(&self, era: u32) -> bool {
+        match *self {
+            EraRange::All => true,
+            EraRange::EraToEnd(start) => era >= start,
+            EraRange::StartToEra(end) => era <= end,
+            EraRange::EraToEra(start, end) => era >= start && era <= end,
+        }
+    }
+
+    /// Staker rewards can only be claimed oldest era first, so the range has to start at
+    /// or before the first claimable era and the claims stop at its end.
+    fn staker_claims(&self, rewards: Vec<EraReward>) -> Result<Vec<EraReward>, ClaimError> {
+        let start = match *self {
+            EraRange::EraToEnd(start) | EraRange::EraToEra(start, _) => start,
+            EraRange::All | EraRange::StartToEra(_) => 0,
+        };
+
+        if let Some(first) = rewards.first().filter(|first| first.era < start) {
+            return Err(ClaimError::StakerEraSkipped(first.era));
+        }
+
+        Ok(rewards
+            .into_iter()
+            .take_while(|reward| self.contains(reward.era))
+            .collect())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn claim_command(
     account: Option<String>,
     core: Option<u32>,
@@ -31,146 +60,216 @@ pub async fn claim_command(
     end: Option<u32>,
     extra: ExtraArgs,
 ) -> Result<(), CliError> {
+    ensure_latest(&extra)?;
+
     let keystore = Keystore::open();
     let signer = get_signer_interactive(&keystore, account)?;
+    let signer_account = signer.account_id().clone();
 
-    let api = OnlineClient::<PolkadotConfig>::from_url(
-        extra.endpoint.unwrap_or(TINKERNET_WEBSOCKET.to_string()),
-    )
-    .await
-    .map_err(|_| ApiError::EndpointConnectionFailed)?;
+    let Connection { api, rpc, token } = connect(extra.endpoint).await?;
 
     let era_range = match (all, start, end) {
         (true, None, None) => EraRange::All,
         (false, Some(start_era), None) => EraRange::EraToEnd(start_era),
         (false, None, Some(end_era)) => EraRange::StartToEra(end_era),
         (false, Some(start_era), Some(end_era)) => EraRange::EraToEra(start_era, end_era),
-        _ => return Err(CliError::Unknown),
+        _ => return Err(ClaimError::InvalidEraRange.into()),
     };
 
-    let (min, max) = if let Some(core_id) = core {
-        match era_range {
-            EraRange::All => {
-                let core_storage_query =
-                    subxt::dynamic::storage("OcifStaking", "CoreEraStake", vec![core_id.into()]);
-
-                let mut results = api
-                    .storage()
-                    .at_latest()
-                    .await
-                    .map_err(|_| ApiError::StorageFailed)?
-                    .iter(core_storage_query)
-                    .await
-                    .map_err(|_| ApiError::StorageFailed)?;
-
-                let (mut min, mut max) = (0, 0);
-
-                while let Some(Ok(kv)) = results.next().await {
-                    if !kv
-                        .value
-                        .as_type::<CoreStakeInfo<u128>>()
-                        .map_err(|_| ApiError::DecodeFailed)?
-                        .reward_claimed
-                    {
-                        if let Some(era) = kv.keys[1].as_u128().map(|k| k as u32) {
-                            if era < min || min == 0 {
-                                min = era
-                            }
-                            if era > max {
-                                max = era
-                            }
-                        }
-                    }
-                }
+    let storage = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?;
 
-                (min, max)
-            }
+    let (claim_calls, prompt): (Vec<RuntimeCall>, String) = match (core, as_staker, staker) {
+        (Some(core_id), false, false) => {
+            let eras: Vec<u32> = core_rewards(&storage, core_id)
+                .await?
+                .into_iter()
+                .map(|reward| reward.era)
+                .filter(|era| era_range.contains(*era))
+                .collect();
 
-            _ => unimplemented!(),
+            (
+                eras.iter()
+                    .map(|era| {
+                        RuntimeCall::OcifStaking(OcifStakingCall::core_claim_rewards {
+                            core_id,
+                            era: *era,
+                        })
+                    })
+                    .collect(),
+                format!(
+                    "Confirm transaction to claim rewards for {} eras for core #{}?",
+                    eras.len(),
+                    core_id
+                ),
+            )
         }
-    } else {
-        unimplemented!()
-    };
 
-    match (core, as_staker, staker) {
-        (Some(core_id), false, false) => {
-            let mut claim_calls: Vec<RuntimeCall> = Vec::new();
+        (Some(core_id), true, false) => {
+            let claims = era_range
+                .staker_claims(staker_rewards(&storage, core_id, signer_account.clone()).await?)?;
 
-            for era in min..max {
-                claim_calls.push(RuntimeCall::OcifStaking(
-                    OcifStakingCall::core_claim_rewards { core_id, era },
-                ))
-            }
+            (
+                staker_claim_calls(core_id, claims.len()),
+                format!(
+                    "Confirm transaction to claim staker rewards for {} eras from core #{}?",
+                    claims.len(),
+                    core_id
+                ),
+            )
+        }
 
-            let proposal_tx = tinkernet::tx().utility().batch(claim_calls);
+        (None, false, true) => {
+            let mut calls = Vec::new();
+            let mut cores = 0;
 
-            let tx = api
-                .tx()
-                .create_signed(&proposal_tx, &signer, Default::default())
-                .await
-                .map_err(|_| ApiError::SigningFailed)?;
+            for core_id in registered_cores(&storage).await? {
+                let claims = era_range.staker_claims(
+                    staker_rewards(&storage, core_id, signer_account.clone()).await?,
+                )?;
 
-            let maybe_fee = tx.partial_fee_estimate().await;
+                if !claims.is_empty() {
+                    calls.append(&mut staker_claim_calls(core_id, claims.len()));
+                    cores += 1;
+                }
+            }
 
-            Confirm::new(
-                format!(
-                    "Confirm transaction to claim rewards for {} eras for core #{}?",
-                    (min..max).len(),
-                    core_id
-                )
-                .as_str(),
-            )
-            .with_default(false)
-            .with_help_message(
-                maybe_fee
-                    .map(|fee| {
-                        format!(
-                            "This transaction will cost approximately {} TNKR in fees.",
-                            planck_to_unit(fee)
-                        )
-                    })
-                    .unwrap_or(String::from("Could not calculate transaction fees."))
-                    .as_str(),
-            )
-            .prompt()
-            .map_err(|_| ClaimError::Rejected)?;
-
-            let events = tx
-                .submit_and_watch()
-                .await
-                .map_err(|_| ApiError::SubmissionFailed)?
-                .wait_for_finalized_success()
-                .await
-                .map_err(ApiError::TransactionNotSuccessful)?;
-
-            let total_claimed: u128 = events
-                .find::<tinkernet::ocif_staking::events::CoreClaimed>()
-                .filter_map(|maybe_event| {
-                    if let Ok(event) = maybe_event {
-                        if event.core == core_id {
-                            Some(event.amount)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .sum();
-
-            eprintln!(
-                "Successfully claimed {} TNKR for core #{}",
-                planck_to_unit(total_claimed),
-                core_id
+            let prompt = format!(
+                "Confirm transaction to claim staker rewards for {} eras from {} cores?",
+                calls.len(),
+                cores
             );
+
+            (calls, prompt)
         }
 
-        (Some(_), true, false) => unimplemented!(),
+        _ => return Err(ClaimError::InvalidTarget.into()),
+    };
+
+    if claim_calls.is_empty() {
+        return Err(ClaimError::NothingToClaim.into());
+    }
+
+    let Some(events) = sign_and_submit(
+        &api,
+        &rpc,
+        &token,
+        &tinkernet::tx().utility().batch(claim_calls),
+        &signer,
+        &extra.submit,
+        prompt,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let core_claimed: u128 = events
+        .find::<tinkernet::ocif_staking::events::CoreClaimed>()
+        .filter_map(|maybe_event| maybe_event.ok())
+        .filter(|event| Some(event.core) == core)
+        .map(|event| event.amount)
+        .sum();
 
-        (None, false, true) => unimplemented!(),
+    let staker_claimed: u128 = events
+        .find::<tinkernet::ocif_staking::events::StakerClaimed>()
+        .filter_map(|maybe_event| maybe_event.ok())
+        .filter(|event| event.staker == signer_account)
+        .map(|event| event.amount)
+        .sum();
 
-        _ => return Err(CliError::Unknown),
+    match core {
+        Some(core_id) if !as_staker => eprintln!(
+            "Successfully claimed {} for core #{}",
+            token.display(core_claimed),
+            core_id
+        ),
+        _ => eprintln!(
+            "Successfully claimed {} in staker rewards",
+            token.display(staker_claimed)
+        ),
     }
 
     Ok(())
 }
+
+/// `staker_claim_rewards` claims a single era per call.
+pub(crate) fn staker_claim_calls(core_id: u32, eras: usize) -> Vec<RuntimeCall> {
+    (0..eras)
+        .map(|_| RuntimeCall::OcifStaking(OcifStakingCall::staker_claim_rewards { core_id }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewards(eras: &[u32]) -> Vec<EraReward> {
+        eras.iter()
+            .map(|era| EraReward {
+                era: *era,
+                amount: 1_000,
+            })
+            .collect()
+    }
+
+    fn eras(claims: &[EraReward]) -> Vec<u32> {
+        claims.iter().map(|reward| reward.era).collect()
+    }
+
+    #[test]
+    fn staker_claims_rejects_range_starting_after_first_era() {
+        let result = EraRange::EraToEnd(6).staker_claims(rewards(&[5, 6, 7]));
+
+        assert!(matches!(result, Err(ClaimError::StakerEraSkipped(5))));
+    }
+
+    #[test]
+    fn staker_claims_stops_at_range_end() {
+        let claims = EraRange::EraToEra(5, 6)
+            .staker_claims(rewards(&[5, 6, 7, 8]))
+            .unwrap();
+
+        assert_eq!(eras(&claims), vec![5, 6]);
+    }
+
+    #[test]
+    fn staker_claims_empty_rewards() {
+        for range in [
+            EraRange::All,
+            EraRange::EraToEnd(3),
+            EraRange::StartToEra(3),
+            EraRange::EraToEra(3, 4),
+        ] {
+            assert!(range.staker_claims(Vec::new()).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn staker_claims_all_and_start_to_era() {
+        assert_eq!(
+            eras(&EraRange::All.staker_claims(rewards(&[5, 6, 7])).unwrap()),
+            vec![5, 6, 7]
+        );
+        assert_eq!(
+            eras(
+                &EraRange::StartToEra(6)
+                    .staker_claims(rewards(&[5, 6, 7]))
+                    .unwrap()
+            ),
+            vec![5, 6]
+        );
+    }
+
+    #[test]
+    fn staker_claims_range_starting_at_first_era() {
+        let claims = EraRange::EraToEnd(5)
+            .staker_claims(rewards(&[5, 6, 7]))
+            .unwrap();
+
+        assert_eq!(eras(&claims), vec![5, 6, 7]);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod youdle_consts;
 
 pub const TINKERNET_WEBSOCKET: &str = "wss://tinkernet-rpc.dwellir.com:443";
+pub const TINKERNET_PARA_ID: u32 = 2125;
+pub const KUSAMA_RMRK_GRAPHQL: &str = "https://gql-rmrk2-prod.graphcdn.app/";
@@ -0,0 +1,861 @@
+use crate::{
+    balance::{Balance, Token},
+    commands::{
+        assets::{free_balance, resolve_asset},
+        claim::staker_claim_calls,
+        connect,
+        consts::KUSAMA_RMRK_GRAPHQL,
+        ensure_latest, get_signer_interactive, print_multisig_outcome, resolve_block,
+        sign_and_submit, storage_at, Connection, ExtraArgs,
+    },
+    distribution::{add_weight, allocate, mul_div, policy::Policy, Reputation},
+    error::{ApiError, CliError, DaoError},
+    graphql::GraphqlClient,
+    keystore::Keystore,
+    staking::{core_rewards, staker_rewards, EraReward},
+    tinkernet::{
+        self,
+        ocif_staking::events::{CoreClaimed, StakerClaimed},
+        runtime_types::{
+            frame_system::pallet::Call as SystemCall,
+            pallet_balances::pallet::Call as BalancesCall,
+            pallet_ocif_staking::pallet::Call as OcifStakingCall,
+            pallet_utility::pallet::Call as UtilityCall, tinkernet_runtime::RuntimeCall,
+        },
+    },
+    uniques::collection_items,
+};
+use clap::ValueEnum;
+use parity_scale_codec::Decode;
+use serde::{Deserialize, Serialize};
+use serde_json::Map;
+use std::collections::BTreeMap;
+use subxt::{
+    blocks::ExtrinsicEvents,
+    ext::sp_core::{
+        blake2_256,
+        crypto::{AccountId32, Ss58Codec},
+        sr25519::Pair,
+    },
+    storage::Storage,
+    tx::PairSigner,
+    utils::H256,
+    OnlineClient, PolkadotConfig,
+};
+
+const HOLDERS_QUERY: &str = "query holders($collection: String!, $limit: Int!, $offset: Int!) {
+  nfts(
+    limit: $limit,
+    offset: $offset,
+    where: {
+      collectionId: { _eq: $collection },
+      burned: { _eq: \"\" },
+    },
+    order_by: { id: asc }
+  ) {
+    rootowner
+  }
+}
+";
+
+/// An INV4 core whose staking rewards are distributed, and the account holding its funds.
+#[derive(Debug, Clone)]
+pub struct Dao {
+    pub core_id: u32,
+    pub account: subxt::utils::AccountId32,
+    pub name: String,
+}
+
+impl Dao {
+    /// Looks the core's account up in INV4.
+    pub async fn fetch(
+        storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        core_id: u32,
+    ) -> Result<Self, CliError> {
+        let core = storage
+            .fetch(&tinkernet::storage().inv4().core_storage(core_id))
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .ok_or(DaoError::CoreNotFound(core_id))?;
+
+        Ok(Self {
+            core_id,
+            account: core.account,
+            name: format!("Core #{}", core_id),
+        })
+    }
+}
+
+/// How a distribution orders claiming the DAO's rewards and paying them out.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistributionPhase {
+    /// Claim and pay out the estimated rewards in a single batch.
+    #[default]
+    All,
+    /// Only claim the rewards.
+    Claim,
+    /// Pay out the rewards claimed in `--claim-block`, within the DAO's free balance.
+    Distribute,
+}
+
+/// Where `dao distribute-rewards` reads the holders to pay out from.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolderSource {
+    /// One share per NFT of an RMRK collection on Kusama.
+    Rmrk,
+    /// `address,weight` rows of a CSV file.
+    Csv,
+    /// One share per item of a `Uniques` collection on Tinkernet.
+    Uniques,
+}
+
+#[derive(Debug, Deserialize)]
+struct HolderRecord {
+    address: String,
+    weight: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct HoldersGQLData {
+    nfts: Vec<HolderNft>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HolderNft {
+    rootowner: String,
+}
+
+/// Free native balances of the payees and the native existential deposit, which
+/// `drop_unpayable` checks payouts against.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RecipientBalances {
+    pub existential_deposit: u128,
+    pub free: BTreeMap<String, u128>,
+}
+
+/// A row of the per-address payout sheet.
+#[derive(Debug, Serialize)]
+pub struct PayoutRecord {
+    address: String,
+    amount: Balance,
+    planck: u128,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn dao_distribute_rewards_command(
+    account: Option<String>,
+    core: u32,
+    source: HolderSource,
+    collection: Option<String>,
+    holders: Option<String>,
+    policy: Option<String>,
+    payouts_csv: Option<String>,
+    dry_run: bool,
+    phase: DistributionPhase,
+    claim_block: Option<String>,
+    reserve: String,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let policy = match policy {
+        Some(path) => Policy::load(&path)?,
+        None => Policy::default(),
+    };
+
+    let signer = if dry_run {
+        None
+    } else {
+        ensure_latest(&extra)?;
+
+        let keystore = Keystore::open();
+        Some(get_signer_interactive(&keystore, account)?)
+    };
+
+    let connection = connect(extra.endpoint.clone()).await?;
+    let block_hash = resolve_block(&connection, extra.at.as_deref()).await?;
+
+    let token = connection.token.clone();
+    let storage = connection.api.storage().at(block_hash);
+    let block = block_display(block_hash);
+
+    let dao = Dao::fetch(&storage, core).await?;
+    let core_rewards = core_rewards(&storage, core).await?;
+    let staker_rewards = staker_rewards(&storage, core, dao.account.clone()).await?;
+
+    if phase == DistributionPhase::Claim {
+        return claim_phase(
+            &dao,
+            &token,
+            &block,
+            &core_rewards,
+            &staker_rewards,
+            signer,
+            &extra,
+        )
+        .await;
+    }
+
+    let (claim_block, (unclaimed_core, unclaimed_staker)) = match phase {
+        DistributionPhase::Distribute => {
+            let claim_block = undistributed_claim_block(&connection, &dao, claim_block).await?;
+
+            (
+                Some(claim_block),
+                claimed_budget(
+                    &connection,
+                    &dao,
+                    &policy,
+                    &token,
+                    claim_block,
+                    &reserve,
+                    &extra,
+                )
+                .await?,
+            )
+        }
+        _ => (
+            None,
+            (
+                core_rewards.iter().map(|r| r.amount).sum(),
+                staker_rewards.iter().map(|r| r.amount).sum(),
+            ),
+        ),
+    };
+
+    let weights = match source {
+        HolderSource::Rmrk => {
+            let collection = collection.ok_or(DaoError::MissingHolderInput("--collection"))?;
+
+            let rmrk = GraphqlClient::new(
+                extra
+                    .rmrk_graphql
+                    .clone()
+                    .unwrap_or(KUSAMA_RMRK_GRAPHQL.to_string()),
+            );
+
+            let mut variables = Map::new();
+            variables.insert(String::from("collection"), collection.into());
+
+            let mut weights: BTreeMap<String, Reputation> = BTreeMap::new();
+
+            for nft in rmrk
+                .query_all(
+                    "holders",
+                    HOLDERS_QUERY,
+                    variables,
+                    |data: HoldersGQLData| data.nfts,
+                )
+                .await?
+            {
+                add_weight(&mut weights, nft.rootowner, Reputation::from_units(1))?;
+            }
+
+            weights
+        }
+
+        HolderSource::Csv => {
+            let path = holders.ok_or(DaoError::MissingHolderInput("--holders"))?;
+
+            let mut rdr =
+                csv::Reader::from_path(&path).map_err(|source| DaoError::HoldersRead {
+                    path: path.clone(),
+                    source,
+                })?;
+
+            let mut weights: BTreeMap<String, Reputation> = BTreeMap::new();
+
+            for record in rdr.deserialize::<HolderRecord>() {
+                let record = record.map_err(|source| DaoError::HoldersParse {
+                    path: path.clone(),
+                    source,
+                })?;
+
+                add_weight(
+                    &mut weights,
+                    record.address.trim().to_string(),
+                    Reputation::from_units(record.weight),
+                )?;
+            }
+
+            weights
+        }
+
+        HolderSource::Uniques => {
+            let collection = collection.ok_or(DaoError::MissingHolderInput("--collection"))?;
+            let collection_id = collection
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| DaoError::InvalidCollection(collection.clone()))?;
+
+            let mut weights: BTreeMap<String, Reputation> = BTreeMap::new();
+
+            for item in collection_items(&storage, collection_id).await? {
+                add_weight(
+                    &mut weights,
+                    AccountId32::from(item.owner.0).to_ss58check(),
+                    Reputation::from_units(1),
+                )?;
+            }
+
+            weights
+        }
+    };
+
+    if weights.is_empty() {
+        return Err(DaoError::NoHolders.into());
+    }
+
+    let (core_budget, staker_budget, mut remainder_to_stake) =
+        policy.budgets(unclaimed_core, unclaimed_staker)?;
+
+    let mut payouts = allocate(core_budget + staker_budget, &weights)?;
+
+    let balances = recipient_balances(&connection.api, &storage, &token, payouts.keys()).await?;
+
+    drop_unpayable(&token, &balances, &mut payouts, &mut remainder_to_stake);
+
+    if let Some(path) = payouts_csv {
+        write_records(&path, payout_records(&token, &payouts))?;
+    }
+
+    let Some(signer) = signer else {
+        println!(
+            "Dry run at block {}: {} to {} addresses, {} restaked.",
+            block,
+            token.display(payouts.values().sum()),
+            payouts.len(),
+            token.display(remainder_to_stake)
+        );
+
+        return Ok(());
+    };
+
+    let mut calls = match phase {
+        DistributionPhase::All => claim_calls(&dao, &core_rewards, &staker_rewards),
+        _ => Vec::new(),
+    };
+
+    calls.append(&mut payout_calls(&dao, payouts, remainder_to_stake)?);
+    calls.extend(claim_block.map(distribution_remark));
+
+    let Some(events) = propose(
+        &dao,
+        &extra,
+        &signer,
+        RuntimeCall::Utility(UtilityCall::batch_all { calls }),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    print_multisig_outcome(&events, &format!("{} distribution", dao.name))
+}
+
+/// First phase of a two-phase distribution: proposes only the reward claims and, when the
+/// proposal executes right away, reports the amounts actually claimed.
+pub async fn claim_phase(
+    dao: &Dao,
+    token: &Token,
+    block: &str,
+    core_rewards: &[EraReward],
+    staker_rewards: &[EraReward],
+    signer: Option<PairSigner<PolkadotConfig, Pair>>,
+    extra: &ExtraArgs,
+) -> Result<(), CliError> {
+    let Some(signer) = signer else {
+        println!(
+            "Dry run at block {}: claiming an estimated {} in core and {} in staker rewards.",
+            block,
+            token.display(core_rewards.iter().map(|r| r.amount).sum()),
+            token.display(staker_rewards.iter().map(|r| r.amount).sum())
+        );
+
+        return Ok(());
+    };
+
+    let Some(events) = propose(
+        dao,
+        extra,
+        &signer,
+        RuntimeCall::Utility(UtilityCall::batch_all {
+            calls: claim_calls(dao, core_rewards, staker_rewards),
+        }),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    print_multisig_outcome(&events, &format!("{} claim", dao.name))?;
+
+    if events
+        .has::<tinkernet::inv4::events::MultisigExecuted>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+    {
+        let (core, staker) = claimed_amounts(
+            dao,
+            events.find::<CoreClaimed>(),
+            events.find::<StakerClaimed>(),
+        )?;
+
+        println!(
+            "Claimed {} in core and {} in staker rewards in block 0x{}.",
+            token.display(core),
+            token.display(staker),
+            hex::encode(events.block_hash().as_bytes())
+        );
+    }
+
+    println!(
+        "Once the claims have executed, run `--phase distribute` with `--claim-block` set to the block they executed in."
+    );
+
+    Ok(())
+}
+
+/// Resolves `--claim-block` for the distribute phase, refusing a block whose rewards a
+/// pending or executed proposal already distributes.
+pub async fn undistributed_claim_block(
+    connection: &Connection,
+    dao: &Dao,
+    claim_block: Option<String>,
+) -> Result<H256, CliError> {
+    let claim_block = claim_block.ok_or(DaoError::MissingClaimBlock)?;
+    let claim_block = resolve_block(connection, Some(&claim_block)).await?;
+
+    ensure_not_distributed(connection, dao, claim_block).await?;
+
+    Ok(claim_block)
+}
+
+/// Unclaimed (core, staker) amounts the distribute phase feeds to the policy: what the
+/// `CoreClaimed` and `StakerClaimed` events in `claim_block` report, scaled down when the
+/// resulting payouts and restake don't fit in the DAO's free balance minus `reserve`.
+pub async fn claimed_budget(
+    connection: &Connection,
+    dao: &Dao,
+    policy: &Policy,
+    token: &Token,
+    claim_block: H256,
+    reserve: &str,
+    extra: &ExtraArgs,
+) -> Result<(u128, u128), CliError> {
+    let reserve = token
+        .parse(reserve)
+        .ok_or(DaoError::InvalidReserve(reserve.to_string()))?
+        .planck();
+
+    let events = connection
+        .api
+        .events()
+        .at(claim_block)
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let (core, staker) = claimed_amounts(
+        dao,
+        events.find::<CoreClaimed>(),
+        events.find::<StakerClaimed>(),
+    )?;
+
+    if core == 0 && staker == 0 {
+        return Err(DaoError::NoClaimsFound(block_display(claim_block)).into());
+    }
+
+    let free = storage_at(connection, extra.at.as_deref())
+        .await?
+        .fetch_or_default(&tinkernet::storage().system().account(dao.account.clone()))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .data
+        .free;
+
+    let available = free
+        .checked_sub(reserve)
+        .ok_or(DaoError::ReserveExceedsBalance(token.display(free)))?;
+
+    let (core_budget, staker_budget, restake) = policy.budgets(core, staker)?;
+    let needed = core_budget + staker_budget + restake;
+
+    println!(
+        "Claimed {} in core and {} in staker rewards, {} available after the reserve.",
+        token.display(core),
+        token.display(staker),
+        token.display(available)
+    );
+
+    if needed <= available {
+        return Ok((core, staker));
+    }
+
+    eprintln!(
+        "Warning: the policy would pay out {} but only {} is available, scaling the distribution down.",
+        token.display(needed),
+        token.display(available)
+    );
+
+    Ok((
+        mul_div(core, available, needed)?.0,
+        mul_div(staker, available, needed)?.0,
+    ))
+}
+
+/// Remark added to a distribute phase batch, tying its payouts to the block whose claims
+/// they pay out.
+pub fn distribution_remark(claim_block: H256) -> RuntimeCall {
+    RuntimeCall::System(SystemCall::remark {
+        remark: remark_bytes(claim_block),
+    })
+}
+
+fn remark_bytes(claim_block: H256) -> Vec<u8> {
+    format!("distribute:{}", block_display(claim_block)).into_bytes()
+}
+
+/// Whether `call`, possibly batched, carries the remark of a distribution of `claim_block`.
+pub fn distributes(call: &RuntimeCall, claim_block: H256) -> bool {
+    match call {
+        RuntimeCall::Utility(
+            UtilityCall::batch { calls }
+            | UtilityCall::batch_all { calls }
+            | UtilityCall::force_batch { calls },
+        ) => calls.iter().any(|inner| distributes(inner, claim_block)),
+        RuntimeCall::System(SystemCall::remark { remark }) => *remark == remark_bytes(claim_block),
+        _ => false,
+    }
+}
+
+/// Fails when one of the DAO's pending proposals distributes `claim_block`, or one did
+/// since it was claimed. Executed proposals are found by scanning the events of every
+/// block from `claim_block` to the latest one.
+async fn ensure_not_distributed(
+    connection: &Connection,
+    dao: &Dao,
+    claim_block: H256,
+) -> Result<(), CliError> {
+    let Connection { api, rpc, .. } = connection;
+
+    let mut pending = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .iter(tinkernet::storage().inv4().multisig_iter1(dao.core_id))
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    while let Some(kv) = pending.next().await {
+        let operation = kv.map_err(ApiError::StorageFailed)?.value;
+
+        let call = RuntimeCall::decode(&mut &operation.actual_call.0[..])
+            .map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        if distributes(&call, claim_block) {
+            return Err(DaoError::DistributionPending {
+                claim_block: block_display(claim_block),
+                call_hash: format!("0x{}", hex::encode(blake2_256(&operation.actual_call.0))),
+            }
+            .into());
+        }
+    }
+
+    let not_found = || ApiError::BlockNotFound(block_display(claim_block));
+
+    let start = rpc
+        .chain_get_header(Some(claim_block))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .ok_or_else(not_found)?
+        .number;
+
+    let latest = api
+        .blocks()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .number();
+
+    for number in start..=latest {
+        let hash = rpc
+            .chain_get_block_hash(Some(number.into()))
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .ok_or_else(|| ApiError::BlockNotFound(number.to_string()))?;
+
+        let events = api
+            .events()
+            .at(hash)
+            .await
+            .map_err(ApiError::StorageFailed)?;
+
+        for event in events.find::<tinkernet::inv4::events::MultisigExecuted>() {
+            let event = event.map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+            if event.core_id == dao.core_id && distributes(&event.call, claim_block) {
+                return Err(DaoError::AlreadyDistributed {
+                    claim_block: block_display(claim_block),
+                    block: number,
+                }
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn block_display(hash: H256) -> String {
+    format!("0x{}", hex::encode(hash.as_bytes()))
+}
+
+/// Sums the DAO's own core and staker reward claims out of a set of events.
+fn claimed_amounts(
+    dao: &Dao,
+    core_claims: impl Iterator<Item = Result<CoreClaimed, subxt::Error>>,
+    staker_claims: impl Iterator<Item = Result<StakerClaimed, subxt::Error>>,
+) -> Result<(u128, u128), CliError> {
+    let (mut core, mut staker) = (0, 0);
+
+    for claim in core_claims {
+        let claim = claim.map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        if claim.core == dao.core_id {
+            core += claim.amount;
+        }
+    }
+
+    for claim in staker_claims {
+        let claim = claim.map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        if claim.core == dao.core_id && claim.staker == dao.account {
+            staker += claim.amount;
+        }
+    }
+
+    Ok((core, staker))
+}
+
+/// Claims for every era with estimated rewards. Staker rewards are claimed one era per
+/// `staker_claim_rewards` call, oldest first.
+pub fn claim_calls(
+    dao: &Dao,
+    core_rewards: &[EraReward],
+    staker_rewards: &[EraReward],
+) -> Vec<RuntimeCall> {
+    let claim_core_call = RuntimeCall::Utility(UtilityCall::batch {
+        calls: core_rewards
+            .iter()
+            .map(|reward| {
+                RuntimeCall::OcifStaking(OcifStakingCall::core_claim_rewards {
+                    core_id: dao.core_id,
+                    era: reward.era,
+                })
+            })
+            .collect(),
+    });
+
+    let claim_staker_call = RuntimeCall::Utility(UtilityCall::batch {
+        calls: staker_claim_calls(dao.core_id, staker_rewards.len()),
+    });
+
+    vec![claim_core_call, claim_staker_call]
+}
+
+/// Reads the free native balance of every address, and the existential deposit, from `storage`.
+pub async fn recipient_balances<'a>(
+    api: &OnlineClient<PolkadotConfig>,
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    token: &Token,
+    addresses: impl IntoIterator<Item = &'a String>,
+) -> Result<RecipientBalances, CliError> {
+    let native = resolve_asset(api, storage, token, None).await?;
+    let mut free = BTreeMap::new();
+
+    for address in addresses {
+        let account = AccountId32::from_string(address)
+            .map_err(|_| DaoError::InvalidAccount(address.clone()))?;
+
+        free.insert(
+            address.clone(),
+            free_balance(storage, &native, subxt::utils::AccountId32::from(account)).await?,
+        );
+    }
+
+    Ok(RecipientBalances {
+        existential_deposit: native.existential_deposit,
+        free,
+    })
+}
+
+/// Leaves out zero payouts and payouts that would leave a new account below the existential
+/// deposit, which `Balances::transfer` rejects, failing the whole batch with it. What they
+/// would have received is restaked with the remainder instead.
+///
+/// Addresses missing from `balances` are treated as empty accounts.
+pub fn drop_unpayable(
+    token: &Token,
+    balances: &RecipientBalances,
+    payouts: &mut BTreeMap<String, u128>,
+    remainder_to_stake: &mut u128,
+) {
+    payouts.retain(|_, amount| *amount > 0);
+
+    let dropped: Vec<String> = payouts
+        .iter()
+        .filter(|(address, value)| {
+            let free = balances.free.get(*address).copied().unwrap_or_default();
+
+            free.saturating_add(**value) < balances.existential_deposit
+        })
+        .map(|(address, _)| address.clone())
+        .collect();
+
+    for address in dropped {
+        let value = payouts.remove(&address).unwrap_or_default();
+
+        eprintln!(
+            "Warning: leaving out {} for {}, below the existential deposit of {}; it is restaked instead.",
+            token.display(value),
+            address,
+            token.display(balances.existential_deposit)
+        );
+
+        *remainder_to_stake += value;
+    }
+}
+
+/// Transfers to every payee followed by restaking what is left into the core.
+pub fn payout_calls(
+    dao: &Dao,
+    payouts: BTreeMap<String, u128>,
+    remainder_to_stake: u128,
+) -> Result<Vec<RuntimeCall>, CliError> {
+    let mut calls: Vec<RuntimeCall> = Vec::new();
+
+    for (address, value) in payouts {
+        calls.push(RuntimeCall::Balances(BalancesCall::transfer {
+            dest: subxt::ext::sp_runtime::MultiAddress::Id(
+                AccountId32::from_string(&address)
+                    .map_err(|_| DaoError::InvalidAccount(address.clone()))?,
+            )
+            .into(),
+            value,
+        }));
+    }
+
+    calls.push(RuntimeCall::OcifStaking(OcifStakingCall::stake {
+        core_id: dao.core_id,
+        value: remainder_to_stake,
+    }));
+
+    Ok(calls)
+}
+
+/// Proposes `call` as a multisig operation of the DAO and follows it as `--wait` asks.
+pub async fn propose(
+    dao: &Dao,
+    extra: &ExtraArgs,
+    signer: &PairSigner<PolkadotConfig, Pair>,
+    call: RuntimeCall,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, CliError> {
+    let Connection { api, rpc, token } = connect(extra.endpoint.clone()).await?;
+
+    let proposal_tx = tinkernet::tx().inv4().operate_multisig(
+        dao.core_id,
+        None,
+        tinkernet::runtime_types::pallet_inv4::fee_handling::FeeAsset::Native,
+        call,
+    );
+
+    sign_and_submit(
+        &api,
+        &rpc,
+        &token,
+        &proposal_tx,
+        signer,
+        &extra.submit,
+        format!("Confirm multisig proposal for {}?", dao.name),
+    )
+    .await
+}
+
+/// Per-address sheet sorted by amount (largest first) followed by a totals row.
+pub fn payout_records(token: &Token, payouts: &BTreeMap<String, u128>) -> Vec<PayoutRecord> {
+    let mut sorted: Vec<(&String, &u128)> = payouts.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+    let total: u128 = payouts.values().sum();
+
+    sorted
+        .into_iter()
+        .map(|(address, planck)| PayoutRecord {
+            address: address.clone(),
+            amount: token.amount(*planck),
+            planck: *planck,
+        })
+        .chain(std::iter::once(PayoutRecord {
+            address: String::from("TOTAL"),
+            amount: token.amount(total),
+            planck: total,
+        }))
+        .collect()
+}
+
+/// Writes CSV records to `path`, or to stdout when the path is `-`.
+pub fn write_records<T: Serialize>(
+    path: &str,
+    records: impl IntoIterator<Item = T>,
+) -> Result<(), DaoError> {
+    let error = |source: csv::Error| DaoError::ReportWrite {
+        path: path.to_string(),
+        source,
+    };
+
+    let writer: Box<dyn std::io::Write> = if path == "-" {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(std::fs::File::create(path).map_err(|e| error(e.into()))?)
+    };
+
+    let mut wtr = csv::Writer::from_writer(writer);
+
+    for record in records {
+        wtr.serialize(record).map_err(error)?;
+    }
+
+    wtr.flush().map_err(|e| error(e.into()))
+}
+
+pub fn parse_h256(value: &str) -> Option<subxt::utils::H256> {
+    hex::decode(value.trim().trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(subxt::utils::H256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributes_finds_the_remark_of_its_claim_block_in_batches() {
+        let claim_block = H256([1; 32]);
+
+        let call = RuntimeCall::Utility(UtilityCall::batch_all {
+            calls: vec![
+                RuntimeCall::OcifStaking(OcifStakingCall::stake {
+                    core_id: 0,
+                    value: 1,
+                }),
+                distribution_remark(claim_block),
+            ],
+        });
+
+        assert!(distributes(&call, claim_block));
+        assert!(!distributes(&call, H256([2; 32])));
+        assert!(!distributes(
+            &distribution_remark(H256([2; 32])),
+            claim_block
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    commands::{connect, Connection, ExtraArgs},
+    error::{ApiError, CliError, MetadataError},
+    tinkernet::{self, runtime_types::sp_version::RuntimeVersion},
+};
+use parity_scale_codec::Decode;
+use subxt::{Metadata, OnlineClient, PolkadotConfig};
+
+/// Metadata the `tinkernet` module was generated from.
+const COMPILED_METADATA: &[u8] = include_bytes!("../../metadata.scale");
+
+/// Spec version of the runtime the `tinkernet` module was generated from, read from the
+/// `System::Version` constant of the compiled metadata.
+fn compiled_spec_version() -> Option<u32> {
+    let metadata = Metadata::decode(&mut &COMPILED_METADATA[..]).ok()?;
+    let version = metadata
+        .pallet_by_name("System")?
+        .constant_by_name("Version")?;
+
+    RuntimeVersion::decode(&mut version.value())
+        .ok()
+        .map(|version| version.spec_version)
+}
+
+/// Describes how the node's runtime differs from the one the CLI was built against, or
+/// `None` when the node's metadata still matches the compiled one.
+pub fn metadata_mismatch(api: &OnlineClient<PolkadotConfig>) -> Option<String> {
+    if tinkernet::is_codegen_valid_for(&api.metadata()) {
+        return None;
+    }
+
+    Some(format!(
+        "built against spec version {}, node runs spec version {}",
+        compiled_spec_version()
+            .map(|version| version.to_string())
+            .unwrap_or(String::from("unknown")),
+        api.runtime_version().spec_version
+    ))
+}
+
+/// Saves the node's current metadata so the CLI can be rebuilt against it.
+pub async fn metadata_update_command(output: String, extra: ExtraArgs) -> Result<(), CliError> {
+    let Connection { api, rpc, .. } = connect(extra.endpoint).await?;
+
+    let response = rpc
+        .state_call("Metadata_metadata", None, None)
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let metadata = Vec::<u8>::decode(&mut &response[..])
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+    std::fs::write(&output, metadata).map_err(|source| MetadataError::Write {
+        path: output.clone(),
+        source,
+    })?;
+
+    println!(
+        "Saved the metadata of spec version {} to {}, rebuild the CLI to use it.",
+        api.runtime_version().spec_version,
+        output
+    );
+
+    Ok(())
+}
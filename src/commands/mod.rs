@@ -1,23 +1,58 @@
 use clap::{Parser, Subcommand};
-use inquire::Select;
+use inquire::{Confirm, Select};
+use std::sync::Once;
 use subxt::{
+    backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+    blocks::ExtrinsicEvents,
     ext::sp_core::{crypto::SecretString, sr25519::Pair, Pair as PairTrait},
-    tx::PairSigner,
-    PolkadotConfig,
+    storage::Storage,
+    tx::{PairSigner, TxPayload},
+    utils::H256,
+    OnlineClient, PolkadotConfig,
 };
 
+pub mod assets;
+pub mod balance;
+pub mod chain;
 pub mod claim;
 pub mod consts;
+pub mod dao;
 pub mod insert_key;
+pub mod metadata;
+pub mod nft;
+pub mod rewards;
+pub mod submit;
+pub mod transfer;
+pub mod xcm;
 pub mod youdle_staking_distribution;
 
+pub use assets::assets_list_command;
+pub use balance::balance_command;
+pub use chain::chain_info_command;
 pub use claim::claim_command;
+pub use dao::dao_distribute_rewards_command;
 pub use insert_key::insert_key_command;
-pub use youdle_staking_distribution::youdle_staking_distribution_command;
+pub use metadata::metadata_update_command;
+pub use nft::nft_holders_command;
+pub use rewards::staking_rewards_command;
+pub use transfer::{transfer_batch_command, transfer_command};
+pub use xcm::{rings_send_call_command, rings_transfer_assets_command, xcm_transfer_command};
+pub use youdle_staking_distribution::{
+    youdle_default_policy_command, youdle_snapshot_command, youdle_staking_distribution_command,
+    youdle_verify_command,
+};
 
 use crate::{
-    error::{CliError, KeystoreError},
+    balance::Token,
+    commands::{
+        consts::TINKERNET_WEBSOCKET,
+        dao::{parse_h256, DistributionPhase, HolderSource},
+        submit::{FeePayment, SubmitOptions, WaitFor, DEFAULT_EXPLORER},
+        xcm::XcmChain,
+    },
+    error::{ApiError, CliError, KeystoreError},
     keystore::Keystore,
+    tinkernet,
 };
 
 #[derive(Parser, Debug)]
@@ -28,10 +63,68 @@ pub struct Args {
 
     #[arg(long)]
     pub endpoint: Option<String>,
+
+    /// RMRK GraphQL indexer used for NFT holder data.
+    #[arg(long)]
+    pub rmrk_graphql: Option<String>,
+
+    /// OCIF staking squid used for unclaimed reward totals.
+    #[arg(long)]
+    pub ocif_squid: Option<String>,
+
+    /// Read chain state at this block number or hash instead of the latest block. Indexer
+    /// data is always current, and commands that submit transactions refuse it.
+    #[arg(long, global = true)]
+    pub at: Option<String>,
+
+    /// How long to follow submitted transactions before returning.
+    #[arg(long, global = true, value_enum, default_value_t = WaitFor::Finalized)]
+    pub wait: WaitFor,
+
+    /// Explorer link printed for submitted transactions. `{hash}`, `{block}` and `{index}`
+    /// are replaced with the extrinsic hash, block hash and extrinsic index.
+    #[arg(long, global = true, default_value = DEFAULT_EXPLORER)]
+    pub explorer: String,
+
+    /// Nonce of the first transaction submitted, instead of the account's next index.
+    /// Following transactions of the same run count up from it.
+    #[arg(long, global = true)]
+    pub nonce: Option<u64>,
+
+    /// How many times a transaction dropped from the pool is signed and submitted again.
+    #[arg(long, global = true, default_value_t = 2)]
+    pub retries: u32,
+
+    /// Tip added to every transaction, in units of the native token even with `--fee-asset`.
+    #[arg(long, global = true)]
+    pub tip: Option<String>,
+
+    /// Number of blocks transactions stay valid for. Transactions are immortal by default.
+    #[arg(long, global = true)]
+    pub mortality: Option<u64>,
+
+    /// Asset id or symbol to pay transaction fees in instead of the native token.
+    #[arg(long, global = true)]
+    pub fee_asset: Option<String>,
+
+    /// Submit transactions even when the node's runtime metadata differs from the one the
+    /// CLI was built with.
+    #[arg(long, global = true)]
+    pub allow_outdated_metadata: bool,
 }
 
 pub struct ExtraArgs {
     pub endpoint: Option<String>,
+    pub rmrk_graphql: Option<String>,
+    pub ocif_squid: Option<String>,
+    pub at: Option<String>,
+    pub submit: SubmitOptions,
+}
+
+pub struct Connection {
+    pub api: OnlineClient<PolkadotConfig>,
+    pub rpc: LegacyRpcMethods<PolkadotConfig>,
+    pub token: Token,
 }
 
 #[derive(Subcommand, Debug)]
@@ -43,11 +136,200 @@ pub enum Commands {
         key: String,
     },
 
+    Transfer {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        #[arg(long)]
+        to: String,
+
+        #[arg(long)]
+        amount: String,
+
+        /// Asset id or symbol to transfer instead of the native token.
+        #[arg(long)]
+        asset: Option<String>,
+
+        /// Propose the transfer as a multisig call from this INV4 core instead.
+        #[arg(long)]
+        core: Option<u32>,
+    },
+
+    TransferBatch {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        /// CSV file with `address` and `amount` columns.
+        #[arg(long)]
+        csv: String,
+
+        /// Asset id or symbol to transfer instead of the native token.
+        #[arg(long)]
+        asset: Option<String>,
+
+        /// Propose the batch as a multisig call from this INV4 core instead.
+        #[arg(long)]
+        core: Option<u32>,
+    },
+
+    Balance {
+        /// One or more SS58 addresses to show balances for.
+        #[arg(required = true)]
+        accounts: Vec<String>,
+
+        /// Asset id or symbol to show instead of the native token.
+        #[arg(long)]
+        asset: Option<String>,
+
+        #[arg(long)]
+        json: bool,
+    },
+
+    #[command(subcommand)]
+    Assets(AssetsCommands),
+
+    #[command(subcommand)]
+    Xcm(XcmCommands),
+
+    #[command(subcommand)]
+    Rings(RingsCommands),
+
     #[command(subcommand)]
     Youdles(YoudlesCommands),
 
     #[command(subcommand)]
     Staking(StakingCommands),
+
+    #[command(subcommand)]
+    Dao(DaoCommands),
+
+    #[command(subcommand)]
+    Nft(NftCommands),
+
+    #[command(subcommand)]
+    Metadata(MetadataCommands),
+
+    #[command(subcommand)]
+    Chain(ChainCommands),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AssetsCommands {
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum XcmCommands {
+    /// Send an asset to another chain through XTokens.
+    Transfer {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        /// Asset id or symbol, the native token included.
+        #[arg(long)]
+        asset: String,
+
+        #[arg(long, value_enum)]
+        to_chain: XcmChain,
+
+        /// Receiving account on the destination chain.
+        #[arg(long)]
+        dest: String,
+
+        #[arg(long)]
+        amount: String,
+
+        /// Maximum ref time weight to buy on the destination, unlimited if omitted.
+        #[arg(long)]
+        weight_limit: Option<u64>,
+
+        /// Maximum proof size to buy on the destination along with `--weight-limit`, 0 if
+        /// omitted.
+        #[arg(long, requires = "weight_limit")]
+        weight_proof_size: Option<u64>,
+
+        /// Propose the transfer as a multisig call from this INV4 core instead.
+        #[arg(long)]
+        core: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RingsCommands {
+    /// Propose a call dispatched on another chain from the core's account there.
+    SendCall {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        #[arg(long)]
+        core: u32,
+
+        #[arg(long, value_enum)]
+        chain: XcmChain,
+
+        /// Hex encoded call for the destination chain.
+        #[arg(long)]
+        call: String,
+
+        #[arg(long)]
+        weight_ref_time: u64,
+
+        #[arg(long, default_value_t = 0)]
+        weight_proof_size: u64,
+
+        /// Asset on the destination chain used to pay for execution.
+        #[arg(long)]
+        xcm_fee_asset: String,
+
+        /// Fee in units of the fee asset, or `planck:<amount>`.
+        #[arg(long)]
+        fee: String,
+
+        /// Decimals of the fee asset, for assets not in Tinkernet's asset registry.
+        #[arg(long)]
+        fee_decimals: Option<u32>,
+    },
+
+    /// Propose a transfer of the core's assets held on another chain.
+    TransferAssets {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        #[arg(long)]
+        core: u32,
+
+        #[arg(long, value_enum)]
+        chain: XcmChain,
+
+        #[arg(long)]
+        asset: String,
+
+        /// Amount in units of the asset, or `planck:<amount>`.
+        #[arg(long)]
+        amount: String,
+
+        /// Decimals of the asset, for assets not in Tinkernet's asset registry.
+        #[arg(long)]
+        decimals: Option<u32>,
+
+        #[arg(long)]
+        to: String,
+
+        /// Asset on the destination chain used to pay for execution.
+        #[arg(long)]
+        xcm_fee_asset: String,
+
+        /// Fee in units of the fee asset, or `planck:<amount>`.
+        #[arg(long)]
+        fee: String,
+
+        /// Decimals of the fee asset, for assets not in Tinkernet's asset registry.
+        #[arg(long)]
+        fee_decimals: Option<u32>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -55,9 +337,93 @@ pub enum YoudlesCommands {
     DistributeRewards {
         #[arg(short, long)]
         account: Option<String>,
+        /// Write the per-NFT reputation sheet to this CSV file (`-` for stdout).
+        #[arg(long)]
+        nfts_csv: Option<String>,
+
+        /// Write the per-address payout sheet to this CSV file (`-` for stdout).
+        #[arg(long)]
+        payouts_csv: Option<String>,
+
+        /// JSON policy file with the reputation and split rules, defaults to YoudleDAO's.
+        #[arg(long)]
+        policy: Option<String>,
+
+        /// Compute from a file saved by `youdles snapshot` instead of live data.
+        /// A dry run of the `all` or `claim` phase then runs offline.
+        #[arg(long)]
+        from_snapshot: Option<String>,
+
+        /// Only compute and report the distribution, without proposing it.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Fail on any anomaly in the NFT data instead of leaving the affected NFTs out.
+        #[arg(long)]
+        strict: bool,
+
+        /// Write anomalies found in the NFT data to this CSV file.
+        #[arg(long)]
+        report: Option<String>,
+
+        /// Warn when the OCIF squid's unclaimed totals disagree with the chain estimate.
+        #[arg(long)]
+        cross_check: bool,
+
+        /// Claim and pay out in one batch, or in two separate proposals.
+        #[arg(long, value_enum, default_value_t = DistributionPhase::All)]
+        phase: DistributionPhase,
+
+        /// Block (number or hash) in which the claims proposed by `--phase claim` executed.
+        #[arg(long, required_if_eq("phase", "distribute"))]
+        claim_block: Option<String>,
+
+        /// Amount to keep in the DAO account when distributing claimed rewards.
+        #[arg(long, default_value = "0")]
+        reserve: String,
+    },
+
+    /// Compare a pending distribution proposal with a local recomputation.
+    Verify {
+        call_hash: String,
+
+        #[arg(long)]
+        policy: Option<String>,
+
+        /// Recompute from a file saved by `youdles snapshot` instead of live data.
+        #[arg(long)]
+        from_snapshot: Option<String>,
+
+        /// Warn when the OCIF squid's unclaimed totals disagree with the chain estimate.
+        #[arg(long)]
+        cross_check: bool,
+
+        /// Block (number or hash) in which the claims were executed, for proposals made with
+        /// `--phase distribute`.
+        #[arg(long)]
+        claim_block: Option<String>,
+
+        /// Amount the proposal kept in the DAO account, used with `--claim-block`.
+        #[arg(long, default_value = "0")]
+        reserve: String,
+    },
+
+    /// Save the indexer data and chain state a distribution is computed from.
+    Snapshot {
         #[arg(short, long)]
-        csv: Option<Option<String>>,
+        output: String,
+
+        /// JSON policy file whose item collections are fetched, defaults to YoudleDAO's.
+        #[arg(long)]
+        policy: Option<String>,
+
+        /// Warn when the OCIF squid's unclaimed totals disagree with the chain estimate.
+        #[arg(long)]
+        cross_check: bool,
     },
+
+    /// Print the default YoudleDAO policy as a starting point for custom policies.
+    DefaultPolicy,
 }
 
 #[derive(Subcommand, Debug)]
@@ -84,11 +450,109 @@ pub enum StakingCommands {
         #[arg(short, long, conflicts_with = "all", required_unless_present_any = ["start", "all"])]
         end: Option<u32>,
     },
+
+    /// Show claimable rewards per era, estimated from chain state.
+    Rewards {
+        #[arg(long)]
+        core: u32,
+
+        /// Show the rewards of this staker instead of the core's own.
+        #[arg(long)]
+        staker: Option<String>,
+
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DaoCommands {
+    /// Claim a core's staking rewards and propose paying them out to holders.
+    DistributeRewards {
+        #[arg(short, long)]
+        account: Option<String>,
+
+        #[arg(long)]
+        core: u32,
+
+        #[arg(long, value_enum)]
+        source: HolderSource,
+
+        /// RMRK collection id, or `Uniques` collection id.
+        #[arg(long, required_if_eq_any([("source", "rmrk"), ("source", "uniques")]))]
+        collection: Option<String>,
+
+        /// CSV file with `address,weight` rows.
+        #[arg(long, required_if_eq("source", "csv"))]
+        holders: Option<String>,
+
+        /// JSON policy file, only its `split` applies here. Defaults to YoudleDAO's split.
+        #[arg(long)]
+        policy: Option<String>,
+
+        /// Write the per-address payout sheet to this CSV file (`-` for stdout).
+        #[arg(long)]
+        payouts_csv: Option<String>,
+
+        /// Only compute and report the distribution, without proposing it.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Claim and pay out in one batch, or in two separate proposals.
+        #[arg(long, value_enum, default_value_t = DistributionPhase::All)]
+        phase: DistributionPhase,
+
+        /// Block (number or hash) in which the claims proposed by `--phase claim` executed.
+        #[arg(long, required_if_eq("phase", "distribute"))]
+        claim_block: Option<String>,
+
+        /// Amount to keep in the DAO account when distributing claimed rewards.
+        #[arg(long, default_value = "0")]
+        reserve: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NftCommands {
+    /// List the holders of a `Uniques` collection.
+    Holders {
+        #[arg(long)]
+        collection: u32,
+
+        /// Weigh each item by this numeric attribute instead of counting it once.
+        #[arg(long)]
+        weight_attribute: Option<String>,
+
+        #[arg(long)]
+        json: bool,
+
+        /// File to write the holders to (`-` for stdout).
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetadataCommands {
+    /// Save the node's current runtime metadata to rebuild the CLI against.
+    Update {
+        #[arg(short, long, default_value = "metadata.scale")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ChainCommands {
+    /// Show the chain, runtime and node behind the endpoint.
+    Info {
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 pub fn input_keystore_password() -> Result<SecretString, CliError> {
     Ok(SecretString::new(
-        rpassword::prompt_password("Keystore password: ").map_err(|_| CliError::Unknown)?,
+        rpassword::prompt_password("Keystore password: ").map_err(CliError::PasswordInput)?,
     ))
 }
 
@@ -101,9 +565,7 @@ pub fn get_signer_interactive(
     } else {
         let account_list = keystore.account_list();
 
-        let selection = Select::new("Select an account from the keystore:", account_list).prompt();
-
-        selection.map_err(|_| CliError::Unknown)?
+        Select::new("Select an account from the keystore:", account_list).prompt()?
     };
 
     let key = keystore.get(name).ok_or(KeystoreError::KeyNotFound)?;
@@ -113,3 +575,163 @@ pub fn get_signer_interactive(
 
     Ok(PairSigner::new(keypair))
 }
+
+static METADATA_WARNING: Once = Once::new();
+
+pub async fn connect(endpoint: Option<String>) -> Result<Connection, CliError> {
+    let rpc_client = RpcClient::from_url(endpoint.unwrap_or(TINKERNET_WEBSOCKET.to_string()))
+        .await
+        .map_err(ApiError::EndpointConnectionFailed)?;
+
+    let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc_client.clone())
+        .await
+        .map_err(ApiError::EndpointConnectionFailed)?;
+
+    let rpc = LegacyRpcMethods::<PolkadotConfig>::new(rpc_client);
+
+    // Commands connecting more than once warn about the metadata only the first time.
+    if let Some(mismatch) = metadata::metadata_mismatch(&api) {
+        METADATA_WARNING.call_once(|| {
+            eprintln!(
+                "Warning: the node's runtime metadata differs from the one this CLI was built \
+                 with ({}), calls may be rejected or mis-encoded. Run `metadata update` and \
+                 rebuild.",
+                mismatch
+            )
+        });
+    }
+
+    let token = rpc
+        .system_properties()
+        .await
+        .map(|properties| Token::from_properties(&properties))
+        .unwrap_or_default();
+
+    Ok(Connection { api, rpc, token })
+}
+
+pub async fn sign_and_submit<Call: TxPayload>(
+    api: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    token: &Token,
+    call: &Call,
+    signer: &PairSigner<PolkadotConfig, Pair>,
+    options: &SubmitOptions,
+    prompt: String,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, CliError> {
+    let fee = FeePayment::resolve(api, token, options).await?;
+    let nonce = options.nonce.peek(rpc, signer.account_id()).await?;
+    let tx = submit::sign(api, call, signer, nonce, &fee, options).await?;
+
+    let maybe_fee = tx.partial_fee_estimate().await;
+
+    let confirmed = Confirm::new(prompt.as_str())
+        .with_default(false)
+        .with_help_message(
+            maybe_fee
+                .map(|estimate| match fee.asset_id {
+                    None => format!(
+                        "This transaction will cost approximately {} in fees.",
+                        token.display(estimate)
+                    ),
+                    Some(_) => format!(
+                        "This transaction will cost approximately {} in fees, paid in {}.",
+                        token.display(estimate),
+                        fee.token.symbol
+                    ),
+                })
+                .unwrap_or(String::from("Could not calculate transaction fees."))
+                .as_str(),
+        )
+        .prompt()
+        .map_err(|_| CliError::Rejected)?;
+
+    if !confirmed {
+        return Err(CliError::Rejected);
+    }
+
+    submit::submit_with_retries(api, rpc, tx, call, signer, nonce, &fee, options).await
+}
+
+/// Reports the outcome of an `INV4::operate_multisig` call, which either starts a vote
+/// or, when the caller holds enough votes, executes the call right away.
+pub fn print_multisig_outcome(
+    events: &ExtrinsicEvents<PolkadotConfig>,
+    label: &str,
+) -> Result<(), CliError> {
+    if let Some(event) = events
+        .find_first::<tinkernet::inv4::events::MultisigVoteStarted>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+    {
+        println!(
+            "{} proposal created with hash: {}",
+            label,
+            hex::encode(event.call_hash.as_bytes())
+        );
+    } else {
+        let event = events
+            .find_first::<tinkernet::inv4::events::MultisigExecuted>()
+            .map_err(|error| ApiError::DecodeFailed(error.into()))?
+            .ok_or(ApiError::EventNotFound)?;
+
+        println!(
+            "{} proposal executed immediately with hash: {}",
+            label,
+            hex::encode(event.call_hash.as_bytes())
+        );
+    }
+
+    Ok(())
+}
+
+/// Resolves a block number or hash to a block hash, the latest block when `at` is `None`.
+pub async fn resolve_block(connection: &Connection, at: Option<&str>) -> Result<H256, CliError> {
+    let Some(at) = at.map(str::trim) else {
+        return Ok(connection
+            .api
+            .blocks()
+            .at_latest()
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .hash());
+    };
+
+    let not_found = || ApiError::BlockNotFound(at.to_string());
+
+    let hash = match at.parse::<u32>() {
+        Ok(number) => connection
+            .rpc
+            .chain_get_block_hash(Some(number.into()))
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .ok_or_else(not_found)?,
+        Err(_) => parse_h256(at).ok_or_else(not_found)?,
+    };
+
+    connection
+        .rpc
+        .chain_get_header(Some(hash))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .ok_or_else(not_found)?;
+
+    Ok(hash)
+}
+
+/// Chain state at the block given with `--at`, or at the latest block.
+pub async fn storage_at(
+    connection: &Connection,
+    at: Option<&str>,
+) -> Result<Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>, CliError> {
+    let hash = resolve_block(connection, at).await?;
+
+    Ok(connection.api.storage().at(hash))
+}
+
+/// Transactions are always built against the latest state, so `--at` is refused for them.
+pub fn ensure_latest(extra: &ExtraArgs) -> Result<(), CliError> {
+    match extra.at {
+        Some(_) => Err(CliError::HistoricalSubmission),
+        None => Ok(()),
+    }
+}
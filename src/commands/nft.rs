@@ -0,0 +1,100 @@
+use crate::{
+    commands::{connect, dao::write_records, storage_at, ExtraArgs},
+    error::{CliError, NftError},
+    uniques::{collection_items, item_attribute},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use subxt::ext::sp_core::crypto::{AccountId32, Ss58Codec};
+
+#[derive(Serialize, Debug, Clone)]
+struct Holder {
+    address: String,
+    weight: u32,
+    items: Vec<u32>,
+}
+
+/// A row of the holders sheet. Its `address,weight` columns are what
+/// `dao distribute-rewards --source csv` reads.
+#[derive(Serialize, Debug)]
+struct HolderRow {
+    address: String,
+    weight: u32,
+    items: String,
+}
+
+/// Lists the holders of a `Uniques` collection with the number of items each holds, or the sum
+/// of the items' `weight_attribute` values when given.
+pub async fn nft_holders_command(
+    collection: u32,
+    weight_attribute: Option<String>,
+    json: bool,
+    output: String,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let connection = connect(extra.endpoint).await?;
+    let storage = storage_at(&connection, extra.at.as_deref()).await?;
+
+    let mut holders: BTreeMap<String, Holder> = BTreeMap::new();
+
+    for item in collection_items(&storage, collection).await? {
+        let weight = match &weight_attribute {
+            None => 1,
+            Some(key) => match item_attribute(&storage, collection, item.item, key).await? {
+                None => 0,
+                Some(value) => String::from_utf8_lossy(&value)
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| {
+                        NftError::InvalidWeight(item.item, String::from_utf8_lossy(&value).into())
+                    })?,
+            },
+        };
+
+        let address = AccountId32::from(item.owner.0).to_ss58check();
+
+        let holder = holders.entry(address.clone()).or_insert(Holder {
+            address,
+            weight: 0,
+            items: Vec::new(),
+        });
+
+        holder.weight = holder
+            .weight
+            .checked_add(weight)
+            .ok_or_else(|| NftError::WeightOverflow(holder.address.clone()))?;
+        holder.items.push(item.item);
+    }
+
+    let mut holders: Vec<Holder> = holders.into_values().collect();
+    holders.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.address.cmp(&b.address)));
+
+    if json {
+        let contents = serde_json::to_string_pretty(&holders)?;
+
+        if output == "-" {
+            println!("{}", contents);
+        } else {
+            std::fs::write(&output, contents).map_err(|source| NftError::Write {
+                path: output.clone(),
+                source,
+            })?;
+        }
+    } else {
+        write_records(
+            &output,
+            holders.into_iter().map(|holder| HolderRow {
+                address: holder.address,
+                weight: holder.weight,
+                items: holder
+                    .items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            }),
+        )?;
+    }
+
+    Ok(())
+}
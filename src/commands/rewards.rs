@@ -0,0 +1,64 @@
+use crate::{
+    balance::Balance,
+    commands::{connect, storage_at, Connection, ExtraArgs},
+    error::{BalanceError, CliError},
+    staking::{core_rewards, staker_rewards, EraReward},
+};
+use serde::Serialize;
+use subxt::ext::sp_core::crypto::{AccountId32, Ss58Codec};
+
+#[derive(Serialize, Debug, Clone)]
+struct EraRewardRecord {
+    era: u32,
+    amount: Balance,
+}
+
+/// Prints claimable rewards estimated from chain state: the core's own rewards, or the
+/// rewards of `staker` when given.
+pub async fn staking_rewards_command(
+    core: u32,
+    staker: Option<String>,
+    json: bool,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let staker = staker
+        .map(|address| {
+            AccountId32::from_string(address.trim())
+                .map(subxt::utils::AccountId32::from)
+                .map_err(|_| BalanceError::InvalidAddress(address.clone()))
+        })
+        .transpose()?;
+
+    let connection = connect(extra.endpoint).await?;
+    let storage = storage_at(&connection, extra.at.as_deref()).await?;
+    let Connection { token, .. } = connection;
+
+    let rewards: Vec<EraReward> = match staker {
+        Some(account) => staker_rewards(&storage, core, account).await?,
+        None => core_rewards(&storage, core).await?,
+    };
+
+    let records: Vec<EraRewardRecord> = rewards
+        .iter()
+        .map(|reward| EraRewardRecord {
+            era: reward.era,
+            amount: token.amount(reward.amount),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else {
+        for record in &records {
+            println!("era {:<6} {} {}", record.era, record.amount, token.symbol);
+        }
+
+        println!(
+            "total: {} over {} eras",
+            token.display(rewards.iter().map(|reward| reward.amount).sum()),
+            rewards.len()
+        );
+    }
+
+    Ok(())
+}
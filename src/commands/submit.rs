@@ -0,0 +1,448 @@
+use crate::{
+    balance::Token,
+    commands::{assets::resolve_asset, metadata::metadata_mismatch},
+    dispatch::{dispatch_failure, ensure_dispatched},
+    error::{ApiError, CliError, MetadataError},
+    tinkernet,
+};
+use clap::ValueEnum;
+use std::sync::{Arc, Mutex};
+use subxt::{
+    backend::legacy::LegacyRpcMethods,
+    blocks::ExtrinsicEvents,
+    config::polkadot::PolkadotExtrinsicParamsBuilder,
+    events::Phase,
+    ext::sp_core::sr25519::Pair,
+    tx::{PairSigner, SubmittableExtrinsic, TxPayload, TxStatus},
+    utils::AccountId32,
+    OnlineClient, PolkadotConfig,
+};
+
+pub const DEFAULT_EXPLORER: &str = "https://tinkernet.subscan.io/extrinsic/{hash}";
+
+/// How long to follow a submitted transaction before returning.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaitFor {
+    /// Return as soon as the transaction is submitted.
+    None,
+    /// Return once the transaction is included in a block.
+    InBlock,
+    /// Return once the block including the transaction is finalized.
+    #[default]
+    Finalized,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubmitOptions {
+    pub wait: WaitFor,
+    /// Link printed for submitted transactions, `{hash}`, `{block}` and `{index}` are
+    /// replaced with the extrinsic hash, block hash and extrinsic index.
+    pub explorer: String,
+    /// How many times a transaction dropped from the pool is signed and submitted again.
+    pub retries: u32,
+    pub nonce: NonceManager,
+    /// Tip in units of the native token, also when fees are paid in another asset.
+    pub tip: Option<String>,
+    /// Number of blocks the transaction stays valid for, immortal when not given.
+    pub mortality: Option<u64>,
+    /// Asset id or symbol to pay fees in instead of the native token.
+    pub fee_asset: Option<String>,
+    pub allow_outdated_metadata: bool,
+}
+
+/// How the fees of a transaction are paid, resolved from `--fee-asset` and `--tip`.
+#[derive(Debug, Clone)]
+pub struct FeePayment {
+    /// `None` for the native token, otherwise the `AssetRegistry` id paid through
+    /// `ChargeAssetTxPayment`.
+    pub asset_id: Option<u32>,
+    /// Token of the fee asset.
+    pub token: Token,
+    pub native: Token,
+    /// Tip in native planck, which `ChargeAssetTxPayment` converts like the fee itself.
+    pub tip: u128,
+}
+
+impl FeePayment {
+    pub async fn resolve(
+        api: &OnlineClient<PolkadotConfig>,
+        native: &Token,
+        options: &SubmitOptions,
+    ) -> Result<Self, CliError> {
+        let storage = api
+            .storage()
+            .at_latest()
+            .await
+            .map_err(ApiError::StorageFailed)?;
+
+        let asset = resolve_asset(api, &storage, native, options.fee_asset.as_deref()).await?;
+
+        let tip = match &options.tip {
+            None => 0,
+            Some(tip) => native
+                .parse(tip)
+                .map(|tip| tip.planck())
+                .ok_or_else(|| CliError::InvalidTip(tip.clone()))?,
+        };
+
+        Ok(Self {
+            asset_id: asset.id,
+            token: asset.token,
+            native: native.clone(),
+            tip,
+        })
+    }
+}
+
+/// Hands out nonces for the transactions submitted during a run, so several of them can be
+/// submitted in a row without waiting for each to be included.
+#[derive(Debug, Clone, Default)]
+pub struct NonceManager {
+    next: Arc<Mutex<Option<u64>>>,
+}
+
+impl NonceManager {
+    /// Starts counting from `nonce` instead of the account's next index when given.
+    pub fn new(nonce: Option<u64>) -> Self {
+        Self {
+            next: Arc::new(Mutex::new(nonce)),
+        }
+    }
+
+    /// The nonce for the next transaction of `account`, fetched with `system_accountNextIndex`
+    /// until a transaction is committed and counted locally after that. Nothing is reserved,
+    /// so a transaction that is never submitted doesn't leave a gap.
+    pub async fn peek(
+        &self,
+        rpc: &LegacyRpcMethods<PolkadotConfig>,
+        account: &AccountId32,
+    ) -> Result<u64, CliError> {
+        let cached = *self.next.lock().expect("nonce lock poisoned");
+
+        match cached {
+            Some(nonce) => Ok(nonce),
+            None => Self::fetch(rpc, account).await,
+        }
+    }
+
+    /// Records that the pool accepted a transaction with `nonce`.
+    pub fn commit(&self, nonce: u64) {
+        let mut next = self.next.lock().expect("nonce lock poisoned");
+
+        *next = Some(next.map_or(nonce + 1, |next| next.max(nonce + 1)));
+    }
+
+    /// The account's next index according to the node, including transactions in the pool.
+    pub async fn fetch(
+        rpc: &LegacyRpcMethods<PolkadotConfig>,
+        account: &AccountId32,
+    ) -> Result<u64, CliError> {
+        Ok(rpc
+            .system_account_next_index(account)
+            .await
+            .map_err(ApiError::NonceFailed)?)
+    }
+}
+
+/// Signs `call` with the given nonce, tip, fee asset and `--mortality`. Refuses to when the
+/// node's metadata no longer matches the compiled one, unless `--allow-outdated-metadata`.
+pub async fn sign<Call: TxPayload>(
+    api: &OnlineClient<PolkadotConfig>,
+    call: &Call,
+    signer: &PairSigner<PolkadotConfig, Pair>,
+    nonce: u64,
+    fee: &FeePayment,
+    options: &SubmitOptions,
+) -> Result<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>, CliError> {
+    if let Some(mismatch) = metadata_mismatch(api) {
+        if !options.allow_outdated_metadata {
+            return Err(MetadataError::Outdated(mismatch).into());
+        }
+    }
+
+    let mut params = PolkadotExtrinsicParamsBuilder::<PolkadotConfig>::new().nonce(nonce);
+
+    params = match fee.asset_id {
+        Some(asset_id) => params.tip_of(fee.tip, asset_id),
+        None => params.tip(fee.tip),
+    };
+
+    if let Some(blocks) = options.mortality {
+        let block = api
+            .blocks()
+            .at_latest()
+            .await
+            .map_err(ApiError::StorageFailed)?;
+
+        params = params.mortal(block.header(), blocks);
+    }
+
+    Ok(api
+        .tx()
+        .create_signed(call, signer, params.build())
+        .await
+        .map_err(ApiError::SigningFailed)?)
+}
+
+/// Submits `tx` as [`submit`] does and, when it is dropped from the pool, signs `call` again
+/// with a higher tip and resubmits it under the same nonce, up to `options.retries` times.
+///
+/// A dropped transaction may still have made it into a block, e.g. when the status stream
+/// ends or finality times out. So when the node's next index has moved past the nonce,
+/// nothing is resubmitted and the user is pointed to the transaction to check instead.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_with_retries<Call: TxPayload>(
+    api: &OnlineClient<PolkadotConfig>,
+    rpc: &LegacyRpcMethods<PolkadotConfig>,
+    tx: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    call: &Call,
+    signer: &PairSigner<PolkadotConfig, Pair>,
+    nonce: u64,
+    fee: &FeePayment,
+    options: &SubmitOptions,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, CliError> {
+    let mut tx = tx;
+    let mut fee = fee.clone();
+    let mut attempt = 0;
+
+    loop {
+        match submit(api, tx, nonce, &fee, options).await {
+            Err(CliError::Api(ApiError::TransactionDropped {
+                extrinsic_hash,
+                message,
+            })) if attempt < options.retries => {
+                attempt += 1;
+
+                let next = NonceManager::fetch(rpc, signer.account_id()).await?;
+
+                if next > nonce {
+                    return Err(ApiError::NonceConsumed(extrinsic_hash).into());
+                }
+
+                // A nonce ahead of the account's next index can't be fixed by resubmitting.
+                if next < nonce {
+                    return Err(ApiError::TransactionDropped {
+                        extrinsic_hash,
+                        message,
+                    }
+                    .into());
+                }
+
+                fee.tip += bumped_tip(api, call, signer, nonce, &fee, options).await?;
+
+                eprintln!(
+                    "Transaction dropped ({}), resubmitting with nonce {} and tip {} (attempt {} of {})",
+                    message,
+                    nonce,
+                    fee.native.display(fee.tip),
+                    attempt,
+                    options.retries
+                );
+
+                tx = sign(api, call, signer, nonce, &fee, options).await?;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Tip increase for resubmitting under the same nonce: a tenth of the estimated fee,
+/// at least one planck.
+async fn bumped_tip<Call: TxPayload>(
+    api: &OnlineClient<PolkadotConfig>,
+    call: &Call,
+    signer: &PairSigner<PolkadotConfig, Pair>,
+    nonce: u64,
+    fee: &FeePayment,
+    options: &SubmitOptions,
+) -> Result<u128, CliError> {
+    let estimate = sign(api, call, signer, nonce, fee, options)
+        .await?
+        .partial_fee_estimate()
+        .await
+        .unwrap_or_default();
+
+    Ok((estimate / 10).max(1))
+}
+
+/// Submits a signed transaction and reports its progress until `options.wait` is reached.
+/// The nonce is committed once the pool accepts the transaction.
+/// Returns the transaction's events, or `None` when not waiting for inclusion.
+async fn submit(
+    api: &OnlineClient<PolkadotConfig>,
+    tx: SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    nonce: u64,
+    fee: &FeePayment,
+    options: &SubmitOptions,
+) -> Result<Option<ExtrinsicEvents<PolkadotConfig>>, CliError> {
+    let mut progress = tx
+        .submit_and_watch()
+        .await
+        .map_err(ApiError::SubmissionFailed)?;
+
+    options.nonce.commit(nonce);
+
+    let extrinsic_hash = format!("0x{}", hex::encode(progress.extrinsic_hash().as_bytes()));
+
+    eprintln!("Submitted transaction {}", extrinsic_hash);
+
+    if options.wait == WaitFor::None {
+        eprintln!(
+            "{}",
+            explorer_link(&options.explorer, &extrinsic_hash, "", "")
+        );
+
+        return Ok(None);
+    }
+
+    while let Some(status) = progress.next().await {
+        let in_block = match status.map_err(ApiError::TransactionNotSuccessful)? {
+            TxStatus::Validated => {
+                eprintln!("Validated");
+                continue;
+            }
+            TxStatus::Broadcasted { num_peers } => {
+                eprintln!("Broadcast to {} peers", num_peers);
+                continue;
+            }
+            TxStatus::NoLongerInBestBlock => {
+                eprintln!("No longer in the best block, waiting for another one");
+                continue;
+            }
+            TxStatus::InBestBlock(in_block) => {
+                eprintln!(
+                    "In block 0x{}",
+                    hex::encode(in_block.block_hash().as_bytes())
+                );
+
+                if options.wait != WaitFor::InBlock {
+                    continue;
+                }
+
+                in_block
+            }
+            TxStatus::InFinalizedBlock(in_block) => {
+                eprintln!(
+                    "Finalized in block 0x{}",
+                    hex::encode(in_block.block_hash().as_bytes())
+                );
+
+                in_block
+            }
+            TxStatus::Error { message } | TxStatus::Invalid { message } => {
+                return Err(ApiError::TransactionInvalid(message).into());
+            }
+            TxStatus::Dropped { message } => {
+                return Err(ApiError::TransactionDropped {
+                    extrinsic_hash,
+                    message,
+                }
+                .into());
+            }
+        };
+
+        let events = in_block
+            .wait_for_success()
+            .await
+            .map_err(dispatch_failure)?;
+
+        let (block, index) = report_events(&events, fee, options, &extrinsic_hash)?;
+
+        if let Err(error) = ensure_dispatched(&api.metadata(), &events) {
+            eprintln!(
+                "Included in block {} as extrinsic {}, but the dispatched call failed.",
+                block, index
+            );
+
+            return Err(error);
+        }
+
+        return Ok(Some(events));
+    }
+
+    Err(ApiError::TransactionDropped {
+        extrinsic_hash,
+        message: String::from("transaction status stream ended"),
+    }
+    .into())
+}
+
+/// Prints every event the transaction emitted, the fee paid and an explorer link.
+/// Returns the block hash and extrinsic index the transaction was included at.
+fn report_events(
+    events: &ExtrinsicEvents<PolkadotConfig>,
+    fee: &FeePayment,
+    options: &SubmitOptions,
+    extrinsic_hash: &str,
+) -> Result<(String, String), CliError> {
+    let mut index = None;
+
+    eprintln!("Events:");
+
+    for event in events.iter() {
+        let event = event.map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        if let Phase::ApplyExtrinsic(extrinsic_index) = event.phase() {
+            index = Some(extrinsic_index);
+        }
+
+        eprintln!(
+            "  {}::{} {}",
+            event.pallet_name(),
+            event.variant_name(),
+            event
+                .field_values()
+                .map(|values| values.to_string())
+                .unwrap_or_default()
+        );
+    }
+
+    let native_fee = events
+        .find_first::<tinkernet::transaction_payment::events::TransactionFeePaid>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+        .map(|paid| (paid.actual_fee, paid.tip));
+
+    let asset_fee = events
+        .find_first::<tinkernet::asset_tx_payment::events::AssetTxFeePaid>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+        .map(|paid| (paid.actual_fee, paid.tip));
+
+    if let Some((actual_fee, tip)) = native_fee {
+        eprintln!(
+            "Fee in {}: {} (tip {})",
+            fee.native.symbol,
+            fee.native.display(actual_fee),
+            fee.native.display(tip)
+        );
+    }
+
+    if let Some((actual_fee, tip)) = asset_fee {
+        eprintln!(
+            "Fee paid in {} after conversion: {} (tip {})",
+            fee.token.symbol,
+            fee.token.display(actual_fee),
+            fee.token.display(tip)
+        );
+    }
+
+    let block = format!("0x{}", hex::encode(events.block_hash().as_bytes()));
+    let index = index.map(|index| index.to_string()).unwrap_or_default();
+
+    if !index.is_empty() {
+        eprintln!("Extrinsic index: {}", index);
+    }
+
+    eprintln!(
+        "{}",
+        explorer_link(&options.explorer, extrinsic_hash, &block, &index)
+    );
+
+    Ok((block, index))
+}
+
+fn explorer_link(template: &str, extrinsic_hash: &str, block: &str, index: &str) -> String {
+    template
+        .replace("{hash}", extrinsic_hash)
+        .replace("{block}", block)
+        .replace("{index}", index)
+}
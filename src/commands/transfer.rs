@@ -0,0 +1,299 @@
+use crate::{
+    balance::Token,
+    commands::{
+        assets::{free_balance, resolve_asset},
+        connect, ensure_latest, get_signer_interactive, print_multisig_outcome, sign_and_submit,
+        Connection, ExtraArgs,
+    },
+    error::{ApiError, CliError, TransferError},
+    keystore::Keystore,
+    tinkernet::{
+        self,
+        runtime_types::{
+            orml_tokens::module::Call as TokensCall, pallet_balances::pallet::Call as BalancesCall,
+            pallet_inv4::fee_handling::FeeAsset, pallet_utility::pallet::Call as UtilityCall,
+            tinkernet_runtime::RuntimeCall,
+        },
+    },
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use subxt::ext::sp_core::crypto::{AccountId32, Ss58Codec};
+
+#[derive(Deserialize, Debug, Clone)]
+struct TransferRecord {
+    address: String,
+    amount: String,
+}
+
+#[derive(Debug, Clone)]
+struct Transfer {
+    address: String,
+    dest: AccountId32,
+    value: u128,
+}
+
+pub async fn transfer_command(
+    account: Option<String>,
+    to: String,
+    amount: String,
+    asset: Option<String>,
+    core: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let record = TransferRecord {
+        address: to,
+        amount,
+    };
+
+    send_transfers(account, vec![record], asset, core, extra).await
+}
+
+pub async fn transfer_batch_command(
+    account: Option<String>,
+    csv: String,
+    asset: Option<String>,
+    core: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let mut rdr = csv::Reader::from_path(csv).map_err(TransferError::CsvRead)?;
+
+    let records = rdr
+        .deserialize::<TransferRecord>()
+        .collect::<Result<Vec<TransferRecord>, csv::Error>>()
+        .map_err(TransferError::CsvRead)?;
+
+    if records.is_empty() {
+        return Err(TransferError::EmptyBatch.into());
+    }
+
+    send_transfers(account, records, asset, core, extra).await
+}
+
+fn parse_transfer(record: &TransferRecord, token: &Token) -> Result<Transfer, TransferError> {
+    let address = record.address.trim();
+
+    let dest = AccountId32::from_string(address)
+        .map_err(|_| TransferError::InvalidAddress(address.to_string()))?;
+
+    let value = token
+        .parse(&record.amount)
+        .map(|amount| amount.planck())
+        .filter(|value| *value > 0)
+        .ok_or_else(|| TransferError::InvalidAmount(record.amount.clone()))?;
+
+    Ok(Transfer {
+        address: address.to_string(),
+        dest,
+        value,
+    })
+}
+
+async fn send_transfers(
+    account: Option<String>,
+    records: Vec<TransferRecord>,
+    asset: Option<String>,
+    core: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    ensure_latest(&extra)?;
+
+    let keystore = Keystore::open();
+    let signer = get_signer_interactive(&keystore, account)?;
+
+    let Connection { api, rpc, token } = connect(extra.endpoint).await?;
+
+    let storage = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let asset = resolve_asset(&api, &storage, &token, asset.as_deref()).await?;
+
+    let transfers = records
+        .iter()
+        .map(|record| parse_transfer(record, &asset.token))
+        .collect::<Result<Vec<Transfer>, TransferError>>()?;
+
+    let mut per_recipient: HashMap<AccountId32, (String, u128)> = HashMap::new();
+
+    for transfer in &transfers {
+        let (address, value) = per_recipient
+            .entry(transfer.dest.clone())
+            .or_insert((transfer.address.clone(), 0));
+
+        *value = value
+            .checked_add(transfer.value)
+            .ok_or_else(|| TransferError::InvalidAmount(format!("total for {}", address)))?;
+    }
+
+    for (dest, (address, value)) in per_recipient {
+        let free = free_balance(&storage, &asset, subxt::utils::AccountId32::from(dest)).await?;
+
+        if free.saturating_add(value) < asset.existential_deposit {
+            return Err(TransferError::BelowExistentialDeposit {
+                address,
+                minimum: asset.token.display(asset.existential_deposit),
+            }
+            .into());
+        }
+    }
+
+    let source = if let Some(core_id) = core {
+        storage
+            .fetch(&tinkernet::storage().inv4().core_storage(core_id))
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .ok_or(TransferError::CoreNotFound(core_id))?
+            .account
+    } else {
+        signer.account_id().clone()
+    };
+
+    let source_free = free_balance(&storage, &asset, source).await?;
+
+    let total = transfers
+        .iter()
+        .try_fold(0u128, |total, transfer| total.checked_add(transfer.value))
+        .ok_or_else(|| TransferError::InvalidAmount(String::from("total of the batch")))?;
+
+    if source_free < total.saturating_add(asset.existential_deposit) {
+        return Err(TransferError::InsufficientBalance.into());
+    }
+
+    let description = if transfers.len() == 1 {
+        format!(
+            "transfer {} to {}",
+            asset.token.display(total),
+            transfers[0].address
+        )
+    } else {
+        format!(
+            "transfer a total of {} to {} accounts",
+            asset.token.display(total),
+            transfers.len()
+        )
+    };
+
+    let mut calls: Vec<RuntimeCall> = transfers
+        .iter()
+        .map(|transfer| {
+            let dest = subxt::ext::sp_runtime::MultiAddress::Id(transfer.dest.clone()).into();
+
+            match asset.id {
+                None => RuntimeCall::Balances(BalancesCall::transfer_keep_alive {
+                    dest,
+                    value: transfer.value,
+                }),
+                Some(currency_id) => RuntimeCall::Tokens(TokensCall::transfer {
+                    dest,
+                    currency_id,
+                    amount: transfer.value,
+                }),
+            }
+        })
+        .collect();
+
+    if let Some(core_id) = core {
+        let call = if calls.len() == 1 {
+            calls.remove(0)
+        } else {
+            RuntimeCall::Utility(UtilityCall::batch_all { calls })
+        };
+
+        let proposal_tx =
+            tinkernet::tx()
+                .inv4()
+                .operate_multisig(core_id, None, FeeAsset::Native, call);
+
+        let Some(events) = sign_and_submit(
+            &api,
+            &rpc,
+            &token,
+            &proposal_tx,
+            &signer,
+            &extra.submit,
+            format!(
+                "Confirm multisig proposal for core #{} to {}?",
+                core_id, description
+            ),
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        print_multisig_outcome(&events, "Transfer")?;
+    } else {
+        let prompt = format!("Confirm transaction to {}?", description);
+
+        let Some(events) = (match (calls.len(), asset.id) {
+            (1, None) => {
+                sign_and_submit(
+                    &api,
+                    &rpc,
+                    &token,
+                    &tinkernet::tx().balances().transfer_keep_alive(
+                        subxt::ext::sp_runtime::MultiAddress::Id(transfers[0].dest.clone()).into(),
+                        transfers[0].value,
+                    ),
+                    &signer,
+                    &extra.submit,
+                    prompt,
+                )
+                .await?
+            }
+            (1, Some(currency_id)) => {
+                sign_and_submit(
+                    &api,
+                    &rpc,
+                    &token,
+                    &tinkernet::tx().tokens().transfer(
+                        subxt::ext::sp_runtime::MultiAddress::Id(transfers[0].dest.clone()).into(),
+                        currency_id,
+                        transfers[0].value,
+                    ),
+                    &signer,
+                    &extra.submit,
+                    prompt,
+                )
+                .await?
+            }
+            _ => {
+                sign_and_submit(
+                    &api,
+                    &rpc,
+                    &token,
+                    &tinkernet::tx().utility().batch_all(calls),
+                    &signer,
+                    &extra.submit,
+                    prompt,
+                )
+                .await?
+            }
+        }) else {
+            return Ok(());
+        };
+
+        let transferred: u128 = match asset.id {
+            None => events
+                .find::<tinkernet::balances::events::Transfer>()
+                .filter_map(|maybe_event| maybe_event.ok())
+                .map(|event| event.amount)
+                .sum(),
+            Some(_) => events
+                .find::<tinkernet::tokens::events::Transfer>()
+                .filter_map(|maybe_event| maybe_event.ok())
+                .map(|event| event.amount)
+                .sum(),
+        };
+
+        eprintln!(
+            "Successfully transferred {}",
+            asset.token.display(transferred)
+        );
+    }
+
+    Ok(())
+}
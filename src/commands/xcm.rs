@@ -0,0 +1,516 @@
+use crate::{
+    balance::Token,
+    commands::{
+        assets::resolve_asset, connect, consts::TINKERNET_PARA_ID, ensure_latest,
+        get_signer_interactive, print_multisig_outcome, sign_and_submit, Connection, ExtraArgs,
+    },
+    error::{ApiError, AssetError, CliError, XcmError},
+    keystore::Keystore,
+    tinkernet::{
+        self,
+        runtime_types::{
+            bounded_collections::bounded_vec::BoundedVec,
+            orml_xtokens::module::Call as XTokensCall,
+            pallet_inv4::fee_handling::FeeAsset,
+            pallet_rings::pallet::Call as RingsCall,
+            sp_weights::weight_v2::Weight,
+            tinkernet_runtime::{
+                rings::{
+                    asset_hub::AssetHubAssets, basilisk::BasiliskAssets, karura::KaruraAssets,
+                    kusama::KusamaAssets, moonriver::MoonriverAssets, picasso::PicassoAssets,
+                    shiden::ShidenAssets, ChainAssets, Chains,
+                },
+                RuntimeCall,
+            },
+            xcm::{
+                v3::{
+                    junction::Junction,
+                    junctions::Junctions,
+                    multiasset::{AssetId, Fungibility, MultiAsset},
+                    multilocation::MultiLocation,
+                    WeightLimit,
+                },
+                VersionedMultiAsset, VersionedMultiLocation,
+            },
+        },
+    },
+};
+use clap::ValueEnum;
+use subxt::{
+    ext::sp_core::crypto::{AccountId32, Ss58Codec},
+    storage::Storage,
+    OnlineClient, PolkadotConfig,
+};
+
+/// Destination chains, mirroring the runtime's `rings::Chains` enum. `rings_chain` and the
+/// check below match both ways without wildcards, so regenerating the metadata with a chain
+/// added or removed fails to compile until this list follows.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XcmChain {
+    Basilisk,
+    Picasso,
+    AssetHub,
+    Shiden,
+    Karura,
+    Moonriver,
+    Kusama,
+}
+
+const _: fn(Chains) -> XcmChain = |chain| match chain {
+    Chains::Basilisk => XcmChain::Basilisk,
+    Chains::Picasso => XcmChain::Picasso,
+    Chains::AssetHub => XcmChain::AssetHub,
+    Chains::Shiden => XcmChain::Shiden,
+    Chains::Karura => XcmChain::Karura,
+    Chains::Moonriver => XcmChain::Moonriver,
+    Chains::Kusama => XcmChain::Kusama,
+};
+
+impl XcmChain {
+    fn rings_chain(self) -> Chains {
+        match self {
+            XcmChain::Basilisk => Chains::Basilisk,
+            XcmChain::Picasso => Chains::Picasso,
+            XcmChain::AssetHub => Chains::AssetHub,
+            XcmChain::Shiden => Chains::Shiden,
+            XcmChain::Karura => Chains::Karura,
+            XcmChain::Moonriver => Chains::Moonriver,
+            XcmChain::Kusama => Chains::Kusama,
+        }
+    }
+
+    /// Parachain id on Kusama, `None` for the relay chain itself.
+    /// The metadata doesn't carry these; they follow the runtime's `RingsChain` impls.
+    fn para_id(self) -> Option<u32> {
+        match self {
+            XcmChain::Basilisk => Some(2090),
+            XcmChain::Picasso => Some(2087),
+            XcmChain::AssetHub => Some(1000),
+            XcmChain::Shiden => Some(2007),
+            XcmChain::Karura => Some(2000),
+            XcmChain::Moonriver => Some(2023),
+            XcmChain::Kusama => None,
+        }
+    }
+
+    /// Location of `dest` on this chain as seen from Tinkernet.
+    /// Moonriver uses 20 byte Ethereum style accounts, every other chain uses SS58.
+    fn dest_location(self, dest: &str) -> Result<MultiLocation, XcmError> {
+        let account = if self == XcmChain::Moonriver {
+            let key: [u8; 20] = hex::decode(dest.trim().trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(XcmError::InvalidDestination(dest.to_string()))?;
+
+            Junction::AccountKey20 { network: None, key }
+        } else {
+            let id = AccountId32::from_string(dest.trim())
+                .map_err(|_| XcmError::InvalidDestination(dest.to_string()))?;
+
+            Junction::AccountId32 {
+                network: None,
+                id: id.into(),
+            }
+        };
+
+        Ok(MultiLocation {
+            parents: 1,
+            interior: match self.para_id() {
+                Some(para_id) => Junctions::X2(Junction::Parachain(para_id), account),
+                None => Junctions::X1(account),
+            },
+        })
+    }
+
+    /// Parses an asset name into the `rings::ChainAssets` variant for this chain.
+    /// `Local` assets take an asset id on Asset Hub and a 0x prefixed address on
+    /// Karura and Moonriver.
+    fn chain_asset(self, asset: &str) -> Result<ChainAssets, XcmError> {
+        let unknown = || XcmError::UnknownChainAsset {
+            chain: format!("{:?}", self),
+            asset: asset.to_string(),
+        };
+
+        let local_key = || -> Result<[u8; 20], XcmError> {
+            hex::decode(asset.trim().trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(unknown)
+        };
+
+        let name = asset.trim().to_lowercase();
+
+        Ok(match self {
+            XcmChain::Basilisk => ChainAssets::Basilisk(match name.as_str() {
+                "bsx" => BasiliskAssets::BSX,
+                "tnkr" => BasiliskAssets::TNKR,
+                "ksm" => BasiliskAssets::KSM,
+                "usdt" => BasiliskAssets::USDT,
+                "dai" => BasiliskAssets::DAI,
+                "usdcet" => BasiliskAssets::USDCet,
+                "xrt" => BasiliskAssets::XRT,
+                "ausd" => BasiliskAssets::aUSD,
+                "weth" => BasiliskAssets::wETH,
+                "wbtc" => BasiliskAssets::wBTC,
+                "wusdt" => BasiliskAssets::wUSDT,
+                _ => return Err(unknown()),
+            }),
+            XcmChain::Picasso => ChainAssets::Picasso(match name.as_str() {
+                "pica" => PicassoAssets::PICA,
+                "usdt" => PicassoAssets::USDT,
+                "kusd" => PicassoAssets::kUSD,
+                "ksm" => PicassoAssets::KSM,
+                "tnkr" => PicassoAssets::TNKR,
+                _ => return Err(unknown()),
+            }),
+            XcmChain::AssetHub => ChainAssets::AssetHub(match name.as_str() {
+                "ksm" => AssetHubAssets::KSM,
+                _ => AssetHubAssets::Local(name.parse::<u32>().map_err(|_| unknown())?),
+            }),
+            XcmChain::Shiden => ChainAssets::Shiden(match name.as_str() {
+                "sdn" => ShidenAssets::SDN,
+                "ksm" => ShidenAssets::KSM,
+                _ => return Err(unknown()),
+            }),
+            XcmChain::Karura => ChainAssets::Karura(match name.as_str() {
+                "kar" => KaruraAssets::KAR,
+                "lksm" => KaruraAssets::LKSM,
+                "tksm" => KaruraAssets::tKSM,
+                "ksm" => KaruraAssets::KSM,
+                _ => KaruraAssets::Local(local_key()?),
+            }),
+            XcmChain::Moonriver => ChainAssets::Moonriver(match name.as_str() {
+                "movr" => MoonriverAssets::MOVR,
+                "xcksm" => MoonriverAssets::xcKSM,
+                "xctnkr" => MoonriverAssets::xcTNKR,
+                "xcusdt" => MoonriverAssets::xcUSDT,
+                "xcrmrk" => MoonriverAssets::xcRMRK,
+                "xcaseed" => MoonriverAssets::xcaSeed,
+                "xckar" => MoonriverAssets::xcKAR,
+                "xcvksm" => MoonriverAssets::xcvKSM,
+                "xcvbnc" => MoonriverAssets::xcvBNC,
+                "xcvmovr" => MoonriverAssets::xcvMOVR,
+                "xcbnc" => MoonriverAssets::xcBNC,
+                "xcpha" => MoonriverAssets::xcPHA,
+                "xcsdn" => MoonriverAssets::xcSDN,
+                "xccsm" => MoonriverAssets::xcCSM,
+                "xcteer" => MoonriverAssets::xcTEER,
+                "xcxrt" => MoonriverAssets::xcXRT,
+                "xckma" => MoonriverAssets::xcKMA,
+                "xchko" => MoonriverAssets::xcHKO,
+                "xcpica" => MoonriverAssets::xcPICA,
+                "xckbtc" => MoonriverAssets::xcKBTC,
+                "xckint" => MoonriverAssets::xcKINT,
+                "xccrab" => MoonriverAssets::xcCRAB,
+                "xclit" => MoonriverAssets::xcLIT,
+                "xcmgx" => MoonriverAssets::xcMGX,
+                "xctur" => MoonriverAssets::xcTUR,
+                _ => MoonriverAssets::Local(local_key()?),
+            }),
+            XcmChain::Kusama => ChainAssets::Kusama(match name.as_str() {
+                "ksm" => KusamaAssets::KSM,
+                _ => return Err(unknown()),
+            }),
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn xcm_transfer_command(
+    account: Option<String>,
+    asset: String,
+    to_chain: XcmChain,
+    dest: String,
+    amount: String,
+    weight_limit: Option<u64>,
+    weight_proof_size: Option<u64>,
+    core: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    ensure_latest(&extra)?;
+
+    let keystore = Keystore::open();
+    let signer = get_signer_interactive(&keystore, account)?;
+
+    let Connection { api, rpc, token } = connect(extra.endpoint).await?;
+
+    let storage = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let asset = resolve_asset(&api, &storage, &token, Some(&asset)).await?;
+
+    let value = asset
+        .token
+        .parse(&amount)
+        .map(|amount| amount.planck())
+        .filter(|value| *value > 0)
+        .ok_or(XcmError::InvalidAmount(amount.clone()))?;
+
+    let asset_location = match (asset.id, asset.location) {
+        (None, _) => MultiLocation {
+            parents: 1,
+            interior: Junctions::X1(Junction::Parachain(TINKERNET_PARA_ID)),
+        },
+        (Some(_), Some(VersionedMultiLocation::V3(location))) => location,
+        (Some(_), _) => {
+            return Err(XcmError::UnsupportedAssetLocation(asset.token.symbol.clone()).into())
+        }
+    };
+
+    let multiasset = VersionedMultiAsset::V3(MultiAsset {
+        id: AssetId::Concrete(asset_location),
+        fun: Fungibility::Fungible(value),
+    });
+
+    let dest_location = VersionedMultiLocation::V3(to_chain.dest_location(&dest)?);
+
+    let dest_weight_limit = match weight_limit {
+        Some(ref_time) => WeightLimit::Limited(Weight {
+            ref_time,
+            proof_size: weight_proof_size.unwrap_or_default(),
+        }),
+        None => WeightLimit::Unlimited,
+    };
+
+    let description = format!(
+        "send {} to {} on {:?}",
+        asset.token.display(value),
+        dest.trim(),
+        to_chain
+    );
+
+    if let Some(core_id) = core {
+        let call = RuntimeCall::XTokens(XTokensCall::transfer_multiasset {
+            asset: Box::new(multiasset),
+            dest: Box::new(dest_location),
+            dest_weight_limit,
+        });
+
+        let Some(events) = sign_and_submit(
+            &api,
+            &rpc,
+            &token,
+            &tinkernet::tx()
+                .inv4()
+                .operate_multisig(core_id, None, FeeAsset::Native, call),
+            &signer,
+            &extra.submit,
+            format!(
+                "Confirm multisig proposal for core #{} to {}?",
+                core_id, description
+            ),
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        print_multisig_outcome(&events, "Cross-chain transfer")?;
+    } else {
+        let Some(events) = sign_and_submit(
+            &api,
+            &rpc,
+            &token,
+            &tinkernet::tx().x_tokens().transfer_multiasset(
+                multiasset,
+                dest_location,
+                dest_weight_limit,
+            ),
+            &signer,
+            &extra.submit,
+            format!("Confirm transaction to {}?", description),
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        events
+            .find_first::<tinkernet::x_tokens::events::TransferredMultiAssets>()
+            .map_err(|error| ApiError::DecodeFailed(error.into()))?
+            .ok_or(ApiError::EventNotFound)?;
+
+        eprintln!("Successfully sent {}", description);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn rings_send_call_command(
+    account: Option<String>,
+    core: u32,
+    chain: XcmChain,
+    call: String,
+    weight_ref_time: u64,
+    weight_proof_size: u64,
+    fee_asset: String,
+    fee: String,
+    fee_decimals: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    ensure_latest(&extra)?;
+
+    let keystore = Keystore::open();
+    let signer = get_signer_interactive(&keystore, account)?;
+
+    let Connection { api, rpc, token } = connect(extra.endpoint).await?;
+
+    let storage = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let encoded_call =
+        hex::decode(call.trim().trim_start_matches("0x")).map_err(|_| XcmError::InvalidCall)?;
+
+    let fee_value =
+        parse_chain_amount(&api, &storage, &token, &fee_asset, &fee, fee_decimals).await?;
+
+    let rings_call = RuntimeCall::Rings(RingsCall::send_call {
+        destination: chain.rings_chain(),
+        weight: Weight {
+            ref_time: weight_ref_time,
+            proof_size: weight_proof_size,
+        },
+        fee_asset: chain.chain_asset(&fee_asset)?,
+        fee: fee_value,
+        call: BoundedVec(encoded_call),
+    });
+
+    let Some(events) = sign_and_submit(
+        &api,
+        &rpc,
+        &token,
+        &tinkernet::tx()
+            .inv4()
+            .operate_multisig(core, None, FeeAsset::Native, rings_call),
+        &signer,
+        &extra.submit,
+        format!(
+            "Confirm multisig proposal for core #{} to send a call to {:?} paying {} {} in fees?",
+            core, chain, fee, fee_asset
+        ),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    print_multisig_outcome(&events, "Rings call")
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn rings_transfer_assets_command(
+    account: Option<String>,
+    core: u32,
+    chain: XcmChain,
+    asset: String,
+    amount: String,
+    decimals: Option<u32>,
+    to: String,
+    fee_asset: String,
+    fee: String,
+    fee_decimals: Option<u32>,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    ensure_latest(&extra)?;
+
+    let keystore = Keystore::open();
+    let signer = get_signer_interactive(&keystore, account)?;
+
+    let Connection { api, rpc, token } = connect(extra.endpoint).await?;
+
+    let storage = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let to_account = AccountId32::from_string(to.trim())
+        .map_err(|_| XcmError::InvalidDestination(to.clone()))?;
+
+    let value = parse_chain_amount(&api, &storage, &token, &asset, &amount, decimals).await?;
+    let fee_value =
+        parse_chain_amount(&api, &storage, &token, &fee_asset, &fee, fee_decimals).await?;
+
+    let rings_call = RuntimeCall::Rings(RingsCall::transfer_assets {
+        asset: chain.chain_asset(&asset)?,
+        amount: value,
+        to: to_account.into(),
+        fee_asset: chain.chain_asset(&fee_asset)?,
+        fee: fee_value,
+    });
+
+    let Some(events) = sign_and_submit(
+        &api,
+        &rpc,
+        &token,
+        &tinkernet::tx()
+            .inv4()
+            .operate_multisig(core, None, FeeAsset::Native, rings_call),
+        &signer,
+        &extra.submit,
+        format!(
+            "Confirm multisig proposal for core #{} to transfer {} {} to {} on {:?}?",
+            core,
+            amount,
+            asset,
+            to.trim(),
+            chain
+        ),
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+
+    print_multisig_outcome(&events, "Rings transfer")
+}
+
+/// Parses an amount of an asset on another chain. `planck:<amount>` is taken as is, anything
+/// else is in units of the asset, using `decimals` when given or those of the asset with the
+/// same symbol in Tinkernet's registry.
+///
+/// Numeric ids and 0x addresses name assets local to the destination chain, which the
+/// registry knows nothing about, so they need `decimals` or a planck amount.
+async fn parse_chain_amount(
+    api: &OnlineClient<PolkadotConfig>,
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    native: &Token,
+    symbol: &str,
+    amount: &str,
+    decimals: Option<u32>,
+) -> Result<u128, CliError> {
+    let invalid = || XcmError::InvalidAmount(amount.to_string());
+
+    if let Some(planck) = amount.trim().strip_prefix("planck:") {
+        return Ok(planck.trim().parse::<u128>().map_err(|_| invalid())?);
+    }
+
+    let symbol = symbol.trim();
+    let unknown_decimals = || XcmError::UnknownDecimals(symbol.to_string());
+
+    let token = match decimals {
+        Some(decimals) => Token {
+            symbol: symbol.to_string(),
+            decimals,
+        },
+        None if symbol.parse::<u32>().is_ok() || symbol.starts_with("0x") => {
+            return Err(unknown_decimals().into())
+        }
+        None => match resolve_asset(api, storage, native, Some(symbol)).await {
+            Ok(asset) => asset.token,
+            Err(CliError::Asset(AssetError::UnknownAsset(_))) => {
+                return Err(unknown_decimals().into())
+            }
+            Err(error) => return Err(error),
+        },
+    };
+
+    Ok(token.parse(amount).ok_or_else(invalid)?.planck())
+}
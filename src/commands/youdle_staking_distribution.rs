@@ -1,54 +1,50 @@
 use crate::{
+    balance::Token,
     commands::{
-        consts::{youdle_consts::*, TINKERNET_WEBSOCKET},
-        get_signer_interactive, ExtraArgs,
+        connect,
+        consts::{youdle_consts::*, KUSAMA_RMRK_GRAPHQL},
+        dao::{
+            claim_calls, claim_phase, claimed_budget, distributes, distribution_remark,
+            drop_unpayable, parse_h256, payout_calls, payout_records, propose, recipient_balances,
+            undistributed_claim_block, write_records, Dao, DistributionPhase, RecipientBalances,
+        },
+        ensure_latest, get_signer_interactive, print_multisig_outcome, resolve_block, storage_at,
+        Connection, ExtraArgs,
+    },
+    distribution::{
+        add_weight, allocate,
+        policy::{ItemCollection, ItemKind, Policy},
+        Reputation,
     },
-    error::{ApiError, CliError, YoudleDistError},
+    error::{ApiError, CliError, DistributionError, YoudleDistError},
+    graphql::GraphqlClient,
     keystore::Keystore,
+    staking::{self, EraReward},
     tinkernet::{
         self,
-        ocif_staking::storage::types::general_staker_info::GeneralStakerInfo,
         runtime_types::{
             pallet_balances::pallet::Call as BalancesCall,
-            pallet_ocif_staking::{pallet::Call as OcifStakingCall, primitives::CoreStakeInfo},
-            pallet_utility::pallet::Call as UtilityCall,
-            tinkernet_runtime::RuntimeCall,
+            pallet_ocif_staking::pallet::Call as OcifStakingCall,
+            pallet_utility::pallet::Call as UtilityCall, tinkernet_runtime::RuntimeCall,
         },
     },
 };
-use itertools::{EitherOrBoth, Itertools};
-use parity_scale_codec::Encode;
+use parity_scale_codec::Decode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use subxt::{
-    dynamic::Value,
-    ext::sp_core::crypto::{AccountId32, Ss58Codec},
-    OnlineClient, PolkadotConfig,
-};
+use serde_json::Map;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use subxt::ext::sp_core::crypto::{AccountId32, Ss58Codec};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Youdle {
     id: String,
     owner: String,
-    metadata_properties: Option<Properties>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Properties {
-    #[serde(alias = "Base Reputation")]
-    base_rep: BaseRep,
+    metadata_properties: Option<HashMap<String, Property>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct BaseRep {
-    value: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Eye20 {
-    id: String,
-    rootowner: String,
-    parent: Option<Parent>,
+struct Property {
+    value: serde_json::Value,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -57,31 +53,31 @@ struct Parent {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct Background {
+struct ItemNft {
     id: String,
-    metadata_name: String,
+    metadata_name: Option<String>,
     rootowner: String,
     parent: Option<Parent>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Banner {
+/// An NFT of one of the policy's item collections, reduced to what the policy rules look at.
+#[derive(Debug, Clone)]
+struct Item {
+    kind: ItemKind,
     id: String,
+    name: String,
     rootowner: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct ProcessedBanner {
-    rootowner: String,
-    amount: u32,
+    parent: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct ProcessedYoudle {
     id: String,
     owner: String,
-    core_rep: f32,
-    staker_rep: f32,
+    base_rep: Reputation,
+    core_rep: Reputation,
+    staker_rep: Reputation,
+    modifiers: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -91,8 +87,8 @@ struct AddressRewardPair {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct GQLResponse<Data> {
-    pub data: Data,
+struct NftsGQLData<Nft> {
+    pub nfts: Vec<Nft>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -109,481 +105,1234 @@ struct UnclaimedGQLDataInner {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct YoudlesGQLData {
-    pub backgrounds: Vec<Background>,
-    pub banners: Vec<Banner>,
-    pub eyes: Vec<Eye20>,
+    /// NFTs of each item collection of the policy, by kind.
+    pub items: BTreeMap<ItemKind, Vec<ItemNft>>,
     pub og_youdles: Vec<Youdle>,
     pub youdles: Vec<Youdle>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Variables {}
+/// A row of the per-NFT sheet.
+#[derive(Debug, Serialize)]
+struct NftRecord {
+    id: String,
+    owner: String,
+    base_rep: Reputation,
+    modifiers: String,
+    core_rep: Reputation,
+    staker_rep: Reputation,
+}
 
-#[allow(non_snake_case)]
+/// Everything a distribution is computed from, so it can be saved and recomputed offline.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct GQLQuery {
-    pub operationName: &'static str,
-    pub variables: Variables,
-    pub query: &'static str,
+struct Snapshot {
+    block_hash: String,
+    token_symbol: String,
+    token_decimals: u32,
+    /// Claimable core rewards per era, estimated from chain state.
+    core_rewards: Vec<EraReward>,
+    /// Claimable staker rewards of the DAO account per era, estimated from chain state.
+    staker_rewards: Vec<EraReward>,
+    youdles: YoudlesGQLData,
+    /// Free balances of every youdle owner and the existential deposit, so payouts below
+    /// it are dropped the same way when the distribution is recomputed later.
+    balances: RecipientBalances,
 }
 
-#[derive(Debug, Serialize)]
-struct CsvRecord {
+impl Snapshot {
+    fn token(&self) -> Token {
+        Token {
+            symbol: self.token_symbol.clone(),
+            decimals: self.token_decimals,
+        }
+    }
+
+    /// Estimated (core, staker) rewards left to claim.
+    fn unclaimed(&self) -> (u128, u128) {
+        (
+            self.core_rewards.iter().map(|r| r.amount).sum(),
+            self.staker_rewards.iter().map(|r| r.amount).sum(),
+        )
+    }
+
+    fn read(path: &str) -> Result<Self, YoudleDistError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|source| YoudleDistError::SnapshotRead {
+                path: path.to_string(),
+                source,
+            })?;
+
+        serde_json::from_str(&contents).map_err(|source| YoudleDistError::SnapshotParse {
+            path: path.to_string(),
+            source,
+        })
+    }
+
+    fn write(&self, path: &str) -> Result<(), YoudleDistError> {
+        let contents =
+            serde_json::to_string_pretty(self).map_err(YoudleDistError::SnapshotEncode)?;
+
+        std::fs::write(path, contents).map_err(|source| YoudleDistError::SnapshotWrite {
+            path: path.to_string(),
+            source,
+        })
+    }
+}
+
+struct Distribution {
+    youdle_list: Vec<ProcessedYoudle>,
+    payouts: BTreeMap<String, u128>,
+    remainder_to_stake: u128,
+    anomalies: Vec<Anomaly>,
+}
+
+/// Something in the NFT data the policy could not be applied to. The offending
+/// NFT or item is left out of the distribution.
+#[derive(Serialize, Debug, Clone)]
+struct Anomaly {
+    kind: AnomalyKind,
     id: String,
     owner: String,
-    core_rep: f32,
-    staker_rep: f32,
-    #[serde(rename = "")]
-    empty_0: (),
-    #[serde(rename = "")]
-    empty_1: (),
-    #[serde(rename = "")]
-    empty_2: (),
-    address: Option<String>,
-    reward: Option<u128>,
-    reward_in_units: Option<f64>,
+    detail: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum AnomalyKind {
+    /// The NFT's base reputation property is not a number.
+    InvalidBaseReputation,
+    /// An equipped item whose parent NFT is not in the collection.
+    UnknownParent,
+    /// An unequipped item whose owner holds no NFT left to apply it to.
+    OrphanItem,
+    /// A second equipped item of a kind that already applies to the NFT.
+    StackedItem,
+    /// An upgrade item on an NFT whose reputation no upgrade rule covers.
+    UnexpectedReputation,
+    /// Bonus items held by an owner without any NFT.
+    OwnerWithoutYoudle,
 }
 
+impl Anomaly {
+    fn missing_target(item: &Item) -> Self {
+        match &item.parent {
+            Some(parent) => Self {
+                kind: AnomalyKind::UnknownParent,
+                id: item.id.clone(),
+                owner: item.rootowner.clone(),
+                detail: format!("parent {} not found", parent),
+            },
+            None => Self {
+                kind: AnomalyKind::OrphanItem,
+                id: item.id.clone(),
+                owner: item.rootowner.clone(),
+                detail: String::from("owner holds no eligible youdle"),
+            },
+        }
+    }
+}
+
+pub async fn youdle_snapshot_command(
+    output: String,
+    policy: Option<String>,
+    cross_check: bool,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let policy = match policy {
+        Some(path) => Policy::load(&path)?,
+        None => Policy::default(),
+    };
+
+    let snapshot = take_snapshot(&policy, &extra, cross_check).await?;
+
+    snapshot.write(&output)?;
+
+    println!(
+        "Saved YoudleDAO snapshot at block {} to {}",
+        snapshot.block_hash, output
+    );
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn youdle_staking_distribution_command(
     account: Option<String>,
-    csv: Option<Option<String>>,
+    nfts_csv: Option<String>,
+    payouts_csv: Option<String>,
+    policy: Option<String>,
+    from_snapshot: Option<String>,
+    dry_run: bool,
+    strict: bool,
+    report: Option<String>,
+    cross_check: bool,
+    phase: DistributionPhase,
+    claim_block: Option<String>,
+    reserve: String,
     extra: ExtraArgs,
 ) -> Result<(), CliError> {
-    let keystore = Keystore::open();
-    let signer = get_signer_interactive(&keystore, account)?;
+    let policy = match policy {
+        Some(path) => Policy::load(&path)?,
+        None => Policy::default(),
+    };
 
-    let unclaimed_res: GQLResponse<UnclaimedCoreGQLData> = surf::post(TINKERNET_OCIF_SQUID)
-        .body_json(&UNCLAIMED_CORE_QUERY)
-        .unwrap()
-        .await
-        .unwrap()
-        .body_json()
-        .await
-        .unwrap();
+    let signer = if dry_run {
+        None
+    } else {
+        ensure_latest(&extra)?;
+
+        let keystore = Keystore::open();
+        Some(get_signer_interactive(&keystore, account)?)
+    };
 
-    let unclaimed_core: u128 = unclaimed_res.data.cores[0]
-        .totalUnclaimed
-        .parse::<u128>()
-        .unwrap_or(0u128);
-    let unclaimed_staker: u128 = unclaimed_res.data.stakers[0]
-        .totalUnclaimed
-        .parse::<u128>()
-        .unwrap_or(0u128);
-
-    let api = OnlineClient::<PolkadotConfig>::from_url(
-        extra.endpoint.unwrap_or(TINKERNET_WEBSOCKET.to_string()),
+    let snapshot = match from_snapshot {
+        Some(path) => Snapshot::read(&path)?,
+        None => take_snapshot(&policy, &extra, cross_check).await?,
+    };
+
+    let token = snapshot.token();
+    let dao = youdle_dao();
+
+    if phase == DistributionPhase::Claim {
+        return claim_phase(
+            &dao,
+            &token,
+            &snapshot.block_hash,
+            &snapshot.core_rewards,
+            &snapshot.staker_rewards,
+            signer,
+            &extra,
+        )
+        .await;
+    }
+
+    let (claim_block, unclaimed) = match phase {
+        DistributionPhase::Distribute => {
+            let connection = connect(extra.endpoint.clone()).await?;
+            let claim_block = undistributed_claim_block(&connection, &dao, claim_block).await?;
+
+            (
+                Some(claim_block),
+                claimed_budget(
+                    &connection,
+                    &dao,
+                    &policy,
+                    &token,
+                    claim_block,
+                    &reserve,
+                    &extra,
+                )
+                .await?,
+            )
+        }
+        _ => (None, snapshot.unclaimed()),
+    };
+
+    let Distribution {
+        youdle_list,
+        mut payouts,
+        mut remainder_to_stake,
+        anomalies,
+    } = compute_distribution(&policy, &snapshot, unclaimed)?;
+
+    drop_unpayable(
+        &token,
+        &snapshot.balances,
+        &mut payouts,
+        &mut remainder_to_stake,
+    );
+
+    if !anomalies.is_empty() {
+        eprintln!("Found {} anomalies in the NFT data:", anomalies.len());
+
+        for anomaly in &anomalies {
+            eprintln!(
+                "  {:?} {} ({}): {}",
+                anomaly.kind, anomaly.id, anomaly.owner, anomaly.detail
+            );
+        }
+
+        if let Some(path) = report {
+            write_records(&path, &anomalies)?;
+        }
+
+        if strict {
+            return Err(YoudleDistError::Anomalies(anomalies.len()).into());
+        }
+    }
+
+    if let Some(path) = nfts_csv {
+        write_records(&path, nft_records(&youdle_list)?)?;
+    }
+
+    if let Some(path) = payouts_csv {
+        write_records(&path, payout_records(&token, &payouts))?;
+    }
+
+    let Some(signer) = signer else {
+        println!(
+            "Dry run at block {}: {} to {} addresses, {} restaked.",
+            snapshot.block_hash,
+            token.display(payouts.values().sum()),
+            payouts.len(),
+            token.display(remainder_to_stake)
+        );
+
+        return Ok(());
+    };
+
+    let mut calls = match phase {
+        DistributionPhase::All => {
+            claim_calls(&dao, &snapshot.core_rewards, &snapshot.staker_rewards)
+        }
+        _ => Vec::new(),
+    };
+
+    calls.append(&mut payout_calls(&dao, payouts, remainder_to_stake)?);
+    calls.extend(claim_block.map(distribution_remark));
+
+    let Some(events) = propose(
+        &dao,
+        &extra,
+        &signer,
+        RuntimeCall::Utility(UtilityCall::batch_all { calls }),
     )
-    .await
-    .map_err(|_| ApiError::EndpointConnectionFailed)?;
+    .await?
+    else {
+        return Ok(());
+    };
+
+    print_multisig_outcome(&events, "YoudleDAO distribution")
+}
 
-    let keys: Vec<Value> = vec![YOUDLE_DAO_ID.into()];
-    let core_storage_query = subxt::dynamic::storage("OcifStaking", "CoreEraStake", keys);
+fn youdle_dao() -> Dao {
+    Dao {
+        core_id: YOUDLE_DAO_ID,
+        account: subxt::utils::AccountId32::from(
+            AccountId32::from_string(YOUDLE_DAO_ADDRESS).expect("never fails"),
+        ),
+        name: String::from("YoudleDAO"),
+    }
+}
 
-    let mut results = api
-        .storage()
-        .at_latest()
-        .await
-        .map_err(|_| ApiError::StorageFailed)?
-        .iter(core_storage_query)
+/// Recomputes the distribution and compares it with the transfers in a pending
+/// YoudleDAO multisig proposal, failing when they differ.
+///
+/// With `claim_block` the budget is what the claims in that block paid out minus `reserve`,
+/// as for `--phase distribute`; otherwise it is the snapshot's unclaimed rewards.
+pub async fn youdle_verify_command(
+    call_hash: String,
+    policy: Option<String>,
+    from_snapshot: Option<String>,
+    cross_check: bool,
+    claim_block: Option<String>,
+    reserve: String,
+    extra: ExtraArgs,
+) -> Result<(), CliError> {
+    let policy = match policy {
+        Some(path) => Policy::load(&path)?,
+        None => Policy::default(),
+    };
+
+    let hash = parse_h256(&call_hash).ok_or(YoudleDistError::InvalidCallHash(call_hash.clone()))?;
+
+    let connection = connect(extra.endpoint.clone()).await?;
+
+    let operation = storage_at(&connection, extra.at.as_deref())
+        .await?
+        .fetch(&tinkernet::storage().inv4().multisig(YOUDLE_DAO_ID, hash))
         .await
-        .map_err(|_| ApiError::StorageFailed)?;
+        .map_err(ApiError::StorageFailed)?
+        .ok_or(YoudleDistError::ProposalNotFound(call_hash.clone()))?;
 
-    let (mut min, mut max) = (0, 0);
+    let proposed_call = RuntimeCall::decode(&mut &operation.actual_call.0[..])
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?;
 
-    while let Some(Ok(kv)) = results.next().await {
-        if !kv
-            .value
-            .as_type::<CoreStakeInfo<u128>>()
-            .map_err(|_| ApiError::DecodeFailed)?
-            .reward_claimed
-        {
-            if let Some(era) = kv.keys[1].as_u128().map(|k| k as u32) {
-                if era < min || min == 0 {
-                    min = era
-                }
-                if era > max {
-                    max = era
-                }
+    let mut proposed: BTreeMap<[u8; 32], u128> = BTreeMap::new();
+    let mut proposed_stake: u128 = 0;
+
+    collect_proposed_transfers(&proposed_call, &mut proposed, &mut proposed_stake);
+
+    let snapshot = match from_snapshot {
+        Some(path) => Snapshot::read(&path)?,
+        None => take_snapshot(&policy, &extra, cross_check).await?,
+    };
+
+    let token = snapshot.token();
+
+    let claim_block = match claim_block {
+        Some(claim_block) => Some(resolve_block(&connection, Some(&claim_block)).await?),
+        None => None,
+    };
+
+    let unclaimed = match claim_block {
+        Some(claim_block) => {
+            claimed_budget(
+                &connection,
+                &youdle_dao(),
+                &policy,
+                &token,
+                claim_block,
+                &reserve,
+                &extra,
+            )
+            .await?
+        }
+        None => snapshot.unclaimed(),
+    };
+
+    let Distribution {
+        mut payouts,
+        mut remainder_to_stake,
+        anomalies,
+        ..
+    } = compute_distribution(&policy, &snapshot, unclaimed)?;
+
+    drop_unpayable(
+        &token,
+        &snapshot.balances,
+        &mut payouts,
+        &mut remainder_to_stake,
+    );
+
+    if !anomalies.is_empty() {
+        eprintln!(
+            "Note: {} anomalies in the NFT data were left out of the recomputation.",
+            anomalies.len()
+        );
+    }
+
+    let mut differences = 0;
+
+    for (address, expected) in &payouts {
+        let account = AccountId32::from_string(address)
+            .map_err(|_| YoudleDistError::FailedDecodingAccount)?;
+
+        match proposed.remove(&<[u8; 32]>::from(account)) {
+            None => {
+                differences += 1;
+                println!(
+                    "missing   {}: expected {}",
+                    address,
+                    token.display(*expected)
+                );
+            }
+            Some(amount) if amount != *expected => {
+                differences += 1;
+                println!(
+                    "mismatch  {}: proposed {}, expected {}",
+                    address,
+                    token.display(amount),
+                    token.display(*expected)
+                );
             }
+            Some(_) => {}
         }
     }
 
-    let claim_core_call = RuntimeCall::Utility(UtilityCall::batch {
-        calls: {
-            let mut calls: Vec<RuntimeCall> = Vec::new();
+    for (account, amount) in proposed {
+        differences += 1;
+        println!(
+            "extra     {}: proposed {}",
+            AccountId32::from(account).to_ss58check(),
+            token.display(amount)
+        );
+    }
+
+    if let Some(claim_block) = claim_block {
+        if !distributes(&proposed_call, claim_block) {
+            differences += 1;
+            println!(
+                "remark: the proposal does not distribute the claims of block 0x{}",
+                hex::encode(claim_block.as_bytes())
+            );
+        }
+    }
 
-            for era in min..max {
-                calls.push(RuntimeCall::OcifStaking(
-                    OcifStakingCall::core_claim_rewards {
-                        core_id: YOUDLE_DAO_ID,
-                        era,
-                    },
-                ))
+    if proposed_stake != remainder_to_stake {
+        differences += 1;
+        println!(
+            "restake: proposed {}, expected {}",
+            token.display(proposed_stake),
+            token.display(remainder_to_stake)
+        );
+    }
+
+    if differences > 0 {
+        println!(
+            "Recomputed the distribution at block {}.",
+            snapshot.block_hash
+        );
+
+        return Err(YoudleDistError::ProposalMismatch(differences).into());
+    }
+
+    println!(
+        "Proposal matches the recomputed distribution at block {} ({} recipients, {}).",
+        snapshot.block_hash,
+        payouts.len(),
+        token.display(payouts.values().sum())
+    );
+
+    Ok(())
+}
+
+/// Walks a (possibly batched) call and sums up native transfers per recipient and
+/// the amount staked back into the DAO's core.
+fn collect_proposed_transfers(
+    call: &RuntimeCall,
+    transfers: &mut BTreeMap<[u8; 32], u128>,
+    stake: &mut u128,
+) {
+    match call {
+        RuntimeCall::Utility(
+            UtilityCall::batch { calls }
+            | UtilityCall::batch_all { calls }
+            | UtilityCall::force_batch { calls },
+        ) => {
+            for inner in calls {
+                collect_proposed_transfers(inner, transfers, stake);
+            }
+        }
+        RuntimeCall::Balances(
+            BalancesCall::transfer { dest, value }
+            | BalancesCall::transfer_keep_alive { dest, value }
+            | BalancesCall::transfer_allow_death { dest, value },
+        ) => {
+            if let subxt::utils::MultiAddress::Id(account) = dest {
+                *transfers.entry(account.0).or_default() += value;
             }
+        }
+        RuntimeCall::OcifStaking(OcifStakingCall::stake { core_id, value })
+            if *core_id == YOUDLE_DAO_ID =>
+        {
+            *stake += value;
+        }
+        _ => {}
+    }
+}
 
-            calls
-        },
-    });
+/// Fetches the indexer data, including the policy's item collections, the DAO's staking
+/// state and the youdle owners' balances, pinning chain reads to the `--at` block or the
+/// latest one.
+/// With `cross_check`, the chain's reward estimate is compared against the OCIF squid.
+async fn take_snapshot(
+    policy: &Policy,
+    extra: &ExtraArgs,
+    cross_check: bool,
+) -> Result<Snapshot, CliError> {
+    let connection = connect(extra.endpoint.clone()).await?;
+    let block_hash = resolve_block(&connection, extra.at.as_deref()).await?;
+
+    let Connection { api, token, .. } = connection;
+    let storage = api.storage().at(block_hash);
+
+    let core_rewards = staking::core_rewards(&storage, YOUDLE_DAO_ID).await?;
+    let staker_rewards =
+        staking::staker_rewards(&storage, YOUDLE_DAO_ID, youdle_dao().account).await?;
+
+    if cross_check {
+        cross_check_unclaimed(extra, &token, &core_rewards, &staker_rewards).await?;
+    }
 
-    let staker_storage_query = subxt::dynamic::storage(
-        "OcifStaking",
-        "GeneralStakerInfo",
-        vec![
-            YOUDLE_DAO_ID.into(),
-            AccountId32::from_string(YOUDLE_DAO_ADDRESS)
-                .expect("never fails")
-                .encode()
-                .into(),
-        ],
+    let rmrk = GraphqlClient::new(
+        extra
+            .rmrk_graphql
+            .clone()
+            .unwrap_or(KUSAMA_RMRK_GRAPHQL.to_string()),
     );
 
-    let results = api
-        .storage()
-        .at_latest()
-        .await
-        .unwrap()
-        .fetch(&staker_storage_query)
-        .await
-        .unwrap()
-        .unwrap()
-        .as_type::<GeneralStakerInfo>()
-        .map_err(|_| ApiError::DecodeFailed)?;
+    let mut items = BTreeMap::new();
 
-    let min: u32 = results
-        .stakes
-        .into_iter()
-        .map(|stake| stake.era)
-        .min()
-        .unwrap();
+    for collection in &policy.items {
+        let collection_items = rmrk
+            .query_all("items", ITEMS_QUERY, item_filter(collection), nfts)
+            .await?;
 
-    let claim_staker_call = RuntimeCall::Utility(UtilityCall::batch {
-        calls: {
-            let mut calls: Vec<RuntimeCall> = Vec::new();
+        items.insert(collection.kind.clone(), collection_items);
+    }
 
-            for _ in min..max {
-                calls.push(RuntimeCall::OcifStaking(
-                    OcifStakingCall::staker_claim_rewards {
-                        core_id: YOUDLE_DAO_ID,
-                    },
-                ))
-            }
+    let mut og_youdle_variables = Map::new();
+    og_youdle_variables.insert(String::from("collection"), OG_YOUDLES_COLLECTION.into());
 
-            calls
-        },
-    });
+    let mut youdle_variables = Map::new();
+    youdle_variables.insert(String::from("collection"), YOUDLES_COLLECTION.into());
 
-    let res: GQLResponse<YoudlesGQLData> = surf::post(KUSAMA_RMRK_GRAPHQL)
-        .body_json(&YOUDLES_QUERY)
-        .unwrap()
-        .await
-        .unwrap()
-        .body_json()
-        .await
-        .unwrap();
+    for (part, collection) in YOUDLE_PART_COLLECTIONS {
+        youdle_variables.insert(part.to_string(), collection.into());
+    }
 
-    let data = res.data;
+    let youdles = YoudlesGQLData {
+        items,
+        og_youdles: rmrk
+            .query_all("og_youdles", OG_YOUDLES_QUERY, og_youdle_variables, nfts)
+            .await?,
+        youdles: rmrk
+            .query_all("youdles", YOUDLES_QUERY, youdle_variables, nfts)
+            .await?,
+    };
 
-    let core_rewards: u128 = (unclaimed_core * 60) / 100; // 60%
-    let staker_rewards: u128 = (unclaimed_staker * 60) / 100; // 60%
+    let owners: BTreeSet<String> = youdles
+        .og_youdles
+        .iter()
+        .chain(youdles.youdles.iter())
+        .map(|youdle| youdle.owner.clone())
+        .collect();
+
+    let balances = recipient_balances(&api, &storage, &token, &owners).await?;
+
+    Ok(Snapshot {
+        block_hash: format!("0x{}", hex::encode(block_hash.as_bytes())),
+        token_symbol: token.symbol,
+        token_decimals: token.decimals,
+        core_rewards,
+        staker_rewards,
+        youdles,
+        balances,
+    })
+}
+
+/// Indexer filter selecting the unburned NFTs of an item collection.
+fn item_filter(collection: &ItemCollection) -> Map<String, serde_json::Value> {
+    let mut filter = Map::new();
+
+    filter.insert(String::from("burned"), serde_json::json!({ "_eq": "" }));
+
+    if let Some(symbol) = &collection.symbol {
+        filter.insert(String::from("symbol"), serde_json::json!({ "_eq": symbol }));
+    }
+
+    if let Some(collection_id) = &collection.collection_id {
+        filter.insert(
+            String::from("collectionId"),
+            serde_json::json!({ "_eq": collection_id }),
+        );
+    }
+
+    if let Some(pattern) = &collection.name_pattern {
+        filter.insert(
+            String::from("metadata_name"),
+            serde_json::json!({ "_regex": pattern }),
+        );
+    }
 
-    let remainder_to_stake: u128 = ((unclaimed_core + unclaimed_staker) * 38) / 100; // 38% (to ensure we never use up all balance)
+    let mut variables = Map::new();
+    variables.insert(String::from("where"), serde_json::Value::Object(filter));
 
-    let mut distribution: HashMap<String, u128> = HashMap::new();
+    variables
+}
+
+/// Warns when the OCIF squid's unclaimed totals disagree with the chain estimate.
+async fn cross_check_unclaimed(
+    extra: &ExtraArgs,
+    token: &Token,
+    core_rewards: &[EraReward],
+    staker_rewards: &[EraReward],
+) -> Result<(), CliError> {
+    let squid = GraphqlClient::new(
+        extra
+            .ocif_squid
+            .clone()
+            .unwrap_or(TINKERNET_OCIF_SQUID.to_string()),
+    );
+
+    let unclaimed: UnclaimedCoreGQLData = squid
+        .query(
+            "unclaimed",
+            UNCLAIMED_QUERY,
+            &serde_json::json!({
+                "coreId": YOUDLE_DAO_ID,
+                "account": YOUDLE_DAO_ADDRESS,
+            }),
+        )
+        .await?;
+
+    let indexed = |entries: &[UnclaimedGQLDataInner]| -> u128 {
+        entries
+            .first()
+            .and_then(|entry| entry.totalUnclaimed.parse::<u128>().ok())
+            .unwrap_or(0)
+    };
+
+    for (label, chain, indexer) in [
+        (
+            "core",
+            core_rewards.iter().map(|r| r.amount).sum::<u128>(),
+            indexed(&unclaimed.cores),
+        ),
+        (
+            "staker",
+            staker_rewards.iter().map(|r| r.amount).sum::<u128>(),
+            indexed(&unclaimed.stakers),
+        ),
+    ] {
+        if chain != indexer {
+            eprintln!(
+                "Warning: unclaimed {} rewards differ, chain estimate {} vs indexer {}.",
+                label,
+                token.display(chain),
+                token.display(indexer)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies `policy` to the snapshot's NFTs, splitting the given (core, staker) unclaimed rewards.
+fn compute_distribution(
+    policy: &Policy,
+    snapshot: &Snapshot,
+    (unclaimed_core, unclaimed_staker): (u128, u128),
+) -> Result<Distribution, DistributionError> {
+    let data = snapshot.youdles.clone();
+
+    let (core_rewards, staker_rewards, remainder_to_stake) =
+        policy.budgets(unclaimed_core, unclaimed_staker)?;
 
     let youdle_list: Vec<Youdle> = data
         .og_youdles
         .into_iter()
         .chain(data.youdles.into_iter())
         .collect();
-    let eye20_list: Vec<Eye20> = data.eyes;
-    let bg_list: Vec<Background> = data.backgrounds;
-    let banner_list: Vec<Banner> = data.banners;
 
-    let mut youdle_list: Vec<ProcessedYoudle> = youdle_list
-        .iter()
-        .map(|youdle| {
-            let rep = youdle
-                .metadata_properties
-                .clone()
-                .map(|p| p.base_rep.value.parse::<u32>().unwrap())
-                .unwrap_or(1u32);
-
-            ProcessedYoudle {
-                id: youdle.id.clone(),
-                owner: youdle.owner.clone(),
-                core_rep: rep as f32,
-                staker_rep: rep as f32,
-            }
+    let items: Vec<Item> = data
+        .items
+        .into_iter()
+        .flat_map(|(kind, nfts)| {
+            nfts.into_iter().map(move |nft| Item {
+                kind: kind.clone(),
+                id: nft.id,
+                name: nft.metadata_name.unwrap_or_default(),
+                rootowner: nft.rootowner,
+                parent: nft.parent.map(|p| p.id),
+            })
         })
         .collect();
 
-    for eye in eye20_list {
-        if let Some(parent) = eye.parent.map(|p| p.id) {
-            let (index, found_youdle) = youdle_list
-                .iter()
-                .enumerate()
-                .find(|y| y.1.id == parent)
-                .unwrap();
-
-            let new_rep = match found_youdle.core_rep as u32 {
-                1 | 50 => 100f32,
-                100 => 150f32,
-                _ => panic!("shouldn't happen"),
-            };
-
-            youdle_list[index] = ProcessedYoudle {
-                id: found_youdle.id.clone(),
-                owner: found_youdle.owner.clone(),
-                core_rep: new_rep,
-                staker_rep: new_rep,
-            }
-        } else {
-            let (index, found_youdle) = youdle_list
-                .iter()
-                .enumerate()
-                .rev()
-                .find(|y| y.1.owner == eye.rootowner)
-                .unwrap();
-
-            let new_rep = match found_youdle.core_rep as u32 {
-                1 | 50 => 100f32,
-                100 => 150f32,
-                _ => panic!("shouldn't happen"),
-            };
-
-            youdle_list[index] = ProcessedYoudle {
-                id: found_youdle.id.clone(),
-                owner: found_youdle.owner.clone(),
-                core_rep: new_rep,
-                staker_rep: new_rep,
-            }
+    let mut anomalies: Vec<Anomaly> = Vec::new();
+    let mut processed_youdles: Vec<ProcessedYoudle> = Vec::new();
+
+    for youdle in youdle_list {
+        let rep = match youdle
+            .metadata_properties
+            .as_ref()
+            .and_then(|p| p.get(&policy.base_reputation.property))
+        {
+            None => policy.base_reputation.default,
+            Some(property) => match property_as_u32(&property.value) {
+                Some(rep) => rep,
+                None => {
+                    anomalies.push(Anomaly {
+                        kind: AnomalyKind::InvalidBaseReputation,
+                        id: youdle.id,
+                        owner: youdle.owner,
+                        detail: format!("base reputation {}", property.value),
+                    });
+
+                    continue;
+                }
+            },
+        };
+
+        processed_youdles.push(ProcessedYoudle {
+            id: youdle.id,
+            owner: youdle.owner,
+            base_rep: Reputation::from_units(rep),
+            core_rep: Reputation::from_units(rep),
+            staker_rep: Reputation::from_units(rep),
+            modifiers: Vec::new(),
+        });
+    }
+
+    let mut youdle_list = processed_youdles;
+
+    let upgrade_items: Vec<&Item> = items
+        .iter()
+        .filter(|item| policy.has_upgrades(&item.kind))
+        .collect();
+
+    let upgrades = assign_items(
+        &youdle_list,
+        upgrade_items,
+        |youdle, item| {
+            policy
+                .upgrade(&item.kind, youdle.core_rep)
+                .map(|new_rep| new_rep.raw().saturating_sub(youdle.core_rep.raw()))
+        },
+        &mut anomalies,
+    );
+
+    for (index, item) in upgrades {
+        let Some(new_rep) = policy.upgrade(&item.kind, youdle_list[index].core_rep) else {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::UnexpectedReputation,
+                id: item.id.clone(),
+                owner: item.rootowner.clone(),
+                detail: format!(
+                    "youdle {} has reputation {}",
+                    youdle_list[index].id, youdle_list[index].core_rep
+                ),
+            });
+
+            continue;
+        };
+
+        let youdle = &mut youdle_list[index];
+
+        youdle.modifiers.push(format!(
+            "{} {}: {} -> {}",
+            item.kind, item.id, youdle.core_rep, new_rep
+        ));
+        youdle.core_rep = new_rep;
+        youdle.staker_rep = new_rep;
+    }
+
+    let multiplier_items: Vec<&Item> = items
+        .iter()
+        .filter(|item| !matches!(policy.multipliers(&item.kind, &item.name), Ok((1, 1))))
+        .collect();
+
+    let multipliers = assign_items(
+        &youdle_list,
+        multiplier_items,
+        |youdle, item| {
+            let (core_multiplier, staker_multiplier) =
+                policy.multipliers(&item.kind, &item.name).ok()?;
+
+            let core_gain = youdle.core_rep.checked_mul(core_multiplier - 1).ok()?;
+            let staker_gain = youdle.staker_rep.checked_mul(staker_multiplier - 1).ok()?;
+
+            core_gain.raw().checked_add(staker_gain.raw())
+        },
+        &mut anomalies,
+    );
+
+    for (index, item) in multipliers {
+        let (core_multiplier, staker_multiplier) = policy.multipliers(&item.kind, &item.name)?;
+
+        let youdle = &mut youdle_list[index];
+
+        youdle.modifiers.push(format!(
+            "{} {}: core x{}, staker x{}",
+            item.kind, item.id, core_multiplier, staker_multiplier
+        ));
+        youdle.core_rep = youdle.core_rep.checked_mul(core_multiplier)?;
+        youdle.staker_rep = youdle.staker_rep.checked_mul(staker_multiplier)?;
+    }
+
+    // Percentage bonuses add up per owner and go to the owner's last youdle.
+    let mut bonuses: BTreeMap<String, u128> = BTreeMap::new();
+
+    for item in &items {
+        let percent = policy.bonus_percent(&item.kind);
+
+        if percent > 0 {
+            *bonuses.entry(item.rootowner.clone()).or_default() += percent;
         }
     }
 
-    for bg in bg_list {
-        if let Some(parent) = bg.parent.map(|p| p.id) {
-            let (index, found_youdle) = youdle_list
-                .iter()
-                .enumerate()
-                .find(|y| y.1.id == parent)
-                .unwrap();
-
-            youdle_list[index] = ProcessedYoudle {
-                id: found_youdle.id.clone(),
-                owner: found_youdle.owner.clone(),
-                core_rep: if bg.metadata_name.ends_with("19") {
-                    found_youdle.core_rep * 2.0
-                } else {
-                    found_youdle.core_rep
-                },
-                staker_rep: if bg.metadata_name.ends_with("18") {
-                    found_youdle.staker_rep * 2.0
-                } else {
-                    found_youdle.staker_rep
-                },
-            }
+    for (owner, percent) in bonuses {
+        if let Some(youdle) = youdle_list.iter_mut().rev().find(|y| y.owner == owner) {
+            youdle.modifiers.push(format!("bonus +{}%", percent));
+            youdle.core_rep = youdle.core_rep.scale(100 + percent, 100)?;
+            youdle.staker_rep = youdle.staker_rep.scale(100 + percent, 100)?;
         } else {
-            let (index, found_youdle) = youdle_list
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::OwnerWithoutYoudle,
+                id: String::new(),
+                owner,
+                detail: format!("{}% in bonus items without a youdle", percent),
+            });
+        }
+    }
+
+    let mut core_rep_by_owner: BTreeMap<String, Reputation> = BTreeMap::new();
+    let mut staker_rep_by_owner: BTreeMap<String, Reputation> = BTreeMap::new();
+
+    for youdle in &youdle_list {
+        add_weight(
+            &mut core_rep_by_owner,
+            youdle.owner.clone(),
+            youdle.core_rep,
+        )?;
+        add_weight(
+            &mut staker_rep_by_owner,
+            youdle.owner.clone(),
+            youdle.staker_rep,
+        )?;
+    }
+
+    let mut payouts = allocate(core_rewards, &core_rep_by_owner)?;
+
+    for (owner, reward) in allocate(staker_rewards, &staker_rep_by_owner)? {
+        *payouts.entry(owner).or_default() += reward;
+    }
+
+    Ok(Distribution {
+        youdle_list,
+        payouts,
+        remainder_to_stake,
+        anomalies,
+    })
+}
+
+/// Per-NFT sheet followed by a totals row.
+fn nft_records(youdle_list: &[ProcessedYoudle]) -> Result<Vec<NftRecord>, DistributionError> {
+    let mut records: Vec<NftRecord> = youdle_list
+        .iter()
+        .map(|youdle| NftRecord {
+            id: youdle.id.clone(),
+            owner: youdle.owner.clone(),
+            base_rep: youdle.base_rep,
+            modifiers: youdle.modifiers.join("; "),
+            core_rep: youdle.core_rep,
+            staker_rep: youdle.staker_rep,
+        })
+        .collect();
+
+    records.push(NftRecord {
+        id: String::from("TOTAL"),
+        owner: String::new(),
+        base_rep: Reputation::checked_sum(youdle_list.iter().map(|youdle| youdle.base_rep))?,
+        modifiers: String::new(),
+        core_rep: Reputation::checked_sum(youdle_list.iter().map(|youdle| youdle.core_rep))?,
+        staker_rep: Reputation::checked_sum(youdle_list.iter().map(|youdle| youdle.staker_rep))?,
+    });
+
+    Ok(records)
+}
+
+/// Decides which youdle each upgrade or multiplier item applies to.
+///
+/// - An equipped item always applies to its parent.
+/// - A youdle takes at most one item of each kind, so items don't stack; a second
+///   equipped item of the same kind is reported and ignored.
+/// - Unequipped items go to distinct youdles of their owner that don't have an item
+///   of that kind yet, each one to the youdle it benefits most (earlier youdles win ties).
+///   `benefit` returns `None` for youdles the item can't apply to.
+/// - Items left without an eligible youdle are reported and ignored.
+fn assign_items<'a>(
+    youdle_list: &[ProcessedYoudle],
+    items: Vec<&'a Item>,
+    benefit: impl Fn(&ProcessedYoudle, &Item) -> Option<u128>,
+    anomalies: &mut Vec<Anomaly>,
+) -> Vec<(usize, &'a Item)> {
+    let mut taken: HashSet<(usize, &str)> = HashSet::new();
+    let mut assignments: Vec<(usize, &Item)> = Vec::new();
+    let mut unequipped: BTreeMap<(&str, &str), Vec<&Item>> = BTreeMap::new();
+
+    for item in items {
+        let Some(parent) = &item.parent else {
+            unequipped
+                .entry((item.rootowner.as_str(), item.kind.as_str()))
+                .or_default()
+                .push(item);
+
+            continue;
+        };
+
+        let Some(index) = youdle_list.iter().position(|y| &y.id == parent) else {
+            anomalies.push(Anomaly::missing_target(item));
+            continue;
+        };
+
+        if !taken.insert((index, item.kind.as_str())) {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::StackedItem,
+                id: item.id.clone(),
+                owner: item.rootowner.clone(),
+                detail: format!(
+                    "youdle {} already has an item of this kind",
+                    youdle_list[index].id
+                ),
+            });
+
+            continue;
+        }
+
+        assignments.push((index, item));
+    }
+
+    for ((owner, kind), owner_items) in unequipped {
+        for item in owner_items {
+            let best = youdle_list
                 .iter()
                 .enumerate()
-                .rev()
-                .find(|y| y.1.owner == bg.rootowner)
-                .unwrap();
-
-            youdle_list[index] = ProcessedYoudle {
-                id: found_youdle.id.clone(),
-                owner: found_youdle.owner.clone(),
-                core_rep: if bg.metadata_name.ends_with("19") {
-                    found_youdle.core_rep * 2.0
-                } else {
-                    found_youdle.core_rep
-                },
-                staker_rep: if bg.metadata_name.ends_with("18") {
-                    found_youdle.staker_rep * 2.0
-                } else {
-                    found_youdle.staker_rep
-                },
+                .filter(|(index, youdle)| youdle.owner == owner && !taken.contains(&(*index, kind)))
+                .filter_map(|(index, youdle)| benefit(youdle, item).map(|gain| (gain, index)))
+                .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+            match best {
+                Some((_, index)) => {
+                    taken.insert((index, kind));
+                    assignments.push((index, item));
+                }
+                None => anomalies.push(Anomaly::missing_target(item)),
             }
         }
     }
 
-    let mut h: HashMap<String, ProcessedBanner> = HashMap::new();
+    assignments
+}
 
-    for banner in banner_list {
-        let mut pb = h
-            .get(&banner.rootowner)
-            .unwrap_or(&ProcessedBanner {
-                rootowner: banner.rootowner.clone(),
-                amount: 0,
-            })
-            .clone();
+fn nfts<Nft>(data: NftsGQLData<Nft>) -> Vec<Nft> {
+    data.nfts
+}
+
+fn property_as_u32(value: &serde_json::Value) -> Option<u32> {
+    match value {
+        serde_json::Value::String(value) => value.trim().parse::<u32>().ok(),
+        value => value.as_u64().and_then(|value| u32::try_from(value).ok()),
+    }
+}
 
-        pb.amount += 1;
+pub fn youdle_default_policy_command() -> Result<(), CliError> {
+    println!("{}", serde_json::to_string_pretty(&Policy::default())?);
 
-        h.insert(banner.rootowner, pb);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn youdle(id: &str, owner: &str, rep: u32) -> ProcessedYoudle {
+        ProcessedYoudle {
+            id: id.to_string(),
+            owner: owner.to_string(),
+            base_rep: Reputation::from_units(rep),
+            core_rep: Reputation::from_units(rep),
+            staker_rep: Reputation::from_units(rep),
+            modifiers: Vec::new(),
+        }
     }
 
-    for banner in h.values() {
-        if let Some((index, found_youdle)) = youdle_list
-            .iter()
-            .enumerate()
-            .rev()
-            .find(|y| y.1.owner == banner.rootowner)
-        {
-            youdle_list[index] = ProcessedYoudle {
-                id: found_youdle.id.clone(),
-                owner: found_youdle.owner.clone(),
-                core_rep: found_youdle.core_rep
-                    + ((0.05 * banner.amount as f32) * found_youdle.core_rep),
-                staker_rep: found_youdle.staker_rep
-                    + ((0.05 * banner.amount as f32) * found_youdle.staker_rep),
-            }
+    fn item(id: &str, owner: &str, parent: Option<&str>) -> Item {
+        Item {
+            kind: String::from("eyes"),
+            id: id.to_string(),
+            name: String::new(),
+            rootowner: owner.to_string(),
+            parent: parent.map(str::to_string),
         }
     }
 
-    let total_core_rep: f32 = youdle_list.iter().map(|youdle| youdle.core_rep).sum();
-    let total_staker_rep: f32 = youdle_list.iter().map(|youdle| youdle.staker_rep).sum();
+    /// Assigns `items`, preferring youdles with a higher reputation.
+    fn assign(
+        youdle_list: &[ProcessedYoudle],
+        items: &[Item],
+    ) -> (Vec<(usize, String)>, Vec<Anomaly>) {
+        let mut anomalies = Vec::new();
+
+        let assignments = assign_items(
+            youdle_list,
+            items.iter().collect(),
+            |youdle, _| Some(youdle.core_rep.raw()),
+            &mut anomalies,
+        )
+        .into_iter()
+        .map(|(index, item)| (index, item.id.clone()))
+        .collect();
 
-    for youdle in youdle_list.clone() {
-        let prev_value = distribution.get(&youdle.owner).unwrap_or(&0);
+        (assignments, anomalies)
+    }
 
-        distribution.insert(
-            youdle.owner,
-            prev_value
-                + ((core_rewards / total_core_rep as u128) * youdle.core_rep as u128)
-                + ((staker_rewards / total_staker_rep as u128) * youdle.staker_rep as u128),
-        );
+    #[test]
+    fn equipped_item_stays_on_its_youdle() {
+        let youdles = [youdle("y1", "alice", 1), youdle("y2", "alice", 50)];
+
+        let (assignments, anomalies) = assign(&youdles, &[item("eye", "alice", Some("y1"))]);
+
+        assert_eq!(assignments, vec![(0, String::from("eye"))]);
+        assert!(anomalies.is_empty());
     }
 
-    let mut send_rewards_calls = {
-        let mut calls: Vec<RuntimeCall> = Vec::new();
+    #[test]
+    fn unequipped_item_goes_to_the_owners_best_youdle() {
+        let youdles = [
+            youdle("y1", "alice", 1),
+            youdle("y2", "alice", 50),
+            youdle("y3", "bob", 100),
+        ];
 
-        for (address, value) in distribution.clone() {
-            calls.push(RuntimeCall::Balances(BalancesCall::transfer {
-                dest: subxt::ext::sp_runtime::MultiAddress::Id(
-                    AccountId32::from_string(&address)
-                        .map_err(|_| YoudleDistError::FailedDecodingAccount)?,
-                )
-                .into(),
-                value,
-            }));
-        }
+        let (assignments, anomalies) = assign(&youdles, &[item("eye", "alice", None)]);
 
-        calls
-    };
+        assert_eq!(assignments, vec![(1, String::from("eye"))]);
+        assert!(anomalies.is_empty());
+    }
 
-    let stake_remainder_call = RuntimeCall::OcifStaking(OcifStakingCall::stake {
-        core_id: YOUDLE_DAO_ID,
-        value: remainder_to_stake,
-    });
+    #[test]
+    fn unequipped_items_of_one_owner_go_to_distinct_youdles() {
+        let youdles = [
+            youdle("y1", "alice", 1),
+            youdle("y2", "alice", 50),
+            youdle("y3", "alice", 10),
+        ];
+
+        let (assignments, anomalies) = assign(
+            &youdles,
+            &[item("eye1", "alice", None), item("eye2", "alice", None)],
+        );
 
-    let complete_batch_call = RuntimeCall::Utility(UtilityCall::batch_all {
-        calls: {
-            let mut vec = vec![claim_core_call, claim_staker_call];
+        assert_eq!(
+            assignments,
+            vec![(1, String::from("eye1")), (2, String::from("eye2"))]
+        );
+        assert!(anomalies.is_empty());
+    }
 
-            vec.append(&mut send_rewards_calls);
+    #[test]
+    fn unequipped_items_skip_youdles_with_an_equipped_item() {
+        let youdles = [youdle("y1", "alice", 1), youdle("y2", "alice", 50)];
 
-            vec.push(stake_remainder_call);
+        let (assignments, anomalies) = assign(
+            &youdles,
+            &[
+                item("eye1", "alice", Some("y2")),
+                item("eye2", "alice", None),
+            ],
+        );
 
-            vec
-        },
-    });
+        assert_eq!(
+            assignments,
+            vec![(1, String::from("eye1")), (0, String::from("eye2"))]
+        );
+        assert!(anomalies.is_empty());
+    }
 
-    if let Some(maybe_path) = csv {
-        if let Some(path) = maybe_path {
-            let mut wtr = csv::Writer::from_path(path).unwrap();
+    #[test]
+    fn items_beyond_the_owners_youdles_are_reported() {
+        let youdles = [youdle("y1", "alice", 1), youdle("y2", "alice", 50)];
+
+        let (assignments, anomalies) = assign(
+            &youdles,
+            &[
+                item("eye1", "alice", None),
+                item("eye2", "alice", None),
+                item("eye3", "alice", None),
+                item("eye4", "bob", None),
+            ],
+        );
 
-            write_csv(&mut wtr, youdle_list, distribution);
-        } else {
-            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+        assert_eq!(
+            assignments,
+            vec![(1, String::from("eye1")), (0, String::from("eye2"))]
+        );
+        assert_eq!(anomalies.len(), 2);
+        assert!(anomalies
+            .iter()
+            .all(|anomaly| matches!(anomaly.kind, AnomalyKind::OrphanItem)));
+        assert_eq!(anomalies[0].id, "eye3");
+        assert_eq!(anomalies[1].id, "eye4");
+    }
 
-            write_csv(&mut wtr, youdle_list, distribution);
-        };
+    #[test]
+    fn equipped_items_do_not_stack() {
+        let youdles = [youdle("y1", "alice", 1)];
+
+        let (assignments, anomalies) = assign(
+            &youdles,
+            &[
+                item("eye1", "alice", Some("y1")),
+                item("eye2", "alice", Some("y1")),
+                item("eye3", "alice", Some("y9")),
+            ],
+        );
+
+        assert_eq!(assignments, vec![(0, String::from("eye1"))]);
+        assert!(matches!(anomalies[0].kind, AnomalyKind::StackedItem));
+        assert!(matches!(anomalies[1].kind, AnomalyKind::UnknownParent));
     }
 
-    let proposal_tx = tinkernet::tx().inv4().operate_multisig(
-        YOUDLE_DAO_ID,
-        None,
-        tinkernet::runtime_types::pallet_inv4::fee_handling::FeeAsset::Native,
-        complete_batch_call,
-    );
+    #[test]
+    fn distribution_from_snapshot_fixture() {
+        let snapshot = Snapshot::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/youdle_snapshot.json"
+        ))
+        .unwrap();
 
-    let events = api
-        .tx()
-        .sign_and_submit_then_watch_default(&proposal_tx, &signer)
-        .await
-        .map_err(|_| ApiError::SubmissionFailed)?
-        .wait_for_finalized_success()
-        .await
-        .map_err(ApiError::TransactionNotSuccessful)?;
+        let policy = Policy::default();
+        let unclaimed = snapshot.unclaimed();
+
+        assert_eq!(unclaimed, (2_000_000_000_000, 500_000_000_000));
+
+        let distribution = compute_distribution(&policy, &snapshot, unclaimed).unwrap();
+        let (core_budget, staker_budget, restake) =
+            policy.budgets(unclaimed.0, unclaimed.1).unwrap();
+
+        // Eyes upgrade 50 -> 100, the #0019 background doubles core reputation
+        // and the banner adds 5%.
+        assert_eq!(
+            distribution.payouts,
+            BTreeMap::from([
+                (
+                    String::from("FoQJpPyadYccjavVdTWxpxU7rUEaYhfLCPwXgkfD6Zat9QP"),
+                    26_229_400_635
+                ),
+                (
+                    String::from("Fr4NzY1udSFFLzb2R3qxVQkwz9cZraWkyfH4h3mVVk7BK7P"),
+                    15_313_796_431
+                ),
+                (
+                    String::from("HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"),
+                    1_458_456_802_934
+                ),
+            ])
+        );
+        assert_eq!(
+            distribution.payouts.values().sum::<u128>(),
+            core_budget + staker_budget
+        );
+        assert_eq!(distribution.remainder_to_stake, restake);
+
+        assert_eq!(distribution.anomalies.len(), 1);
+        assert!(matches!(
+            distribution.anomalies[0].kind,
+            AnomalyKind::UnknownParent
+        ));
+        assert_eq!(distribution.anomalies[0].id, "eyes-2");
+    }
 
-    let event = events
-        .find_first::<tinkernet::inv4::events::MultisigVoteStarted>()
-        .map_err(|_| ApiError::EventNotFound)?
-        .ok_or(ApiError::EventNotFound)?;
+    #[test]
+    fn snapshot_balances_drop_payouts_below_the_existential_deposit() {
+        let snapshot = Snapshot::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/youdle_snapshot.json"
+        ))
+        .unwrap();
 
-    println!(
-        "YoudleDAO distribution proposal created with hash: {}",
-        hex::encode(event.call_hash.as_bytes())
-    );
+        let Distribution {
+            mut payouts,
+            mut remainder_to_stake,
+            ..
+        } = compute_distribution(&Policy::default(), &snapshot, snapshot.unclaimed()).unwrap();
 
-    Ok(())
-}
+        let restake = remainder_to_stake;
 
-fn write_csv<W: std::io::Write>(
-    wtr: &mut csv::Writer<W>,
-    youdle_list: Vec<ProcessedYoudle>,
-    distribution: HashMap<String, u128>,
-) {
-    youdle_list
-        .into_iter()
-        .zip_longest(distribution.clone())
-        .for_each(|maybe_both| match maybe_both {
-            EitherOrBoth::Both(
-                ProcessedYoudle {
-                    id,
-                    owner,
-                    core_rep,
-                    staker_rep,
-                },
-                (address, reward),
-            ) => {
-                wtr.serialize(CsvRecord {
-                    id,
-                    owner,
-                    core_rep,
-                    staker_rep,
-                    empty_0: (),
-                    empty_1: (),
-                    empty_2: (),
-                    address: Some(address),
-                    reward: Some(reward),
-                    reward_in_units: Some(reward as f64 / ONE_WITH_DECIMALS as f64),
-                })
-                .unwrap();
-            }
+        drop_unpayable(
+            &snapshot.token(),
+            &snapshot.balances,
+            &mut payouts,
+            &mut remainder_to_stake,
+        );
 
-            EitherOrBoth::Left(ProcessedYoudle {
-                id,
-                owner,
-                core_rep,
-                staker_rep,
-            }) => {
-                wtr.serialize(CsvRecord {
-                    id,
-                    owner,
-                    core_rep,
-                    staker_rep,
-                    empty_0: (),
-                    empty_1: (),
-                    empty_2: (),
-                    address: None,
-                    reward: None,
-                    reward_in_units: None,
-                })
-                .unwrap();
-            }
+        // The youdle-1 owner's payout would leave an empty account below the deposit.
+        assert_eq!(payouts.len(), 2);
+        assert!(!payouts.contains_key("Fr4NzY1udSFFLzb2R3qxVQkwz9cZraWkyfH4h3mVVk7BK7P"));
+        assert_eq!(remainder_to_stake, restake + 15_313_796_431);
+    }
 
-            _ => {}
-        });
+    #[test]
+    fn ties_go_to_the_earlier_youdle() {
+        let youdles = [
+            youdle("y1", "alice", 1),
+            youdle("y2", "alice", 50),
+            youdle("y3", "alice", 50),
+        ];
+
+        let (assignments, _) = assign(&youdles, &[item("eye", "alice", None)]);
+
+        assert_eq!(assignments, vec![(1, String::from("eye"))]);
+    }
 }
@@ -0,0 +1,92 @@
+use crate::{
+    error::{ApiError, CliError},
+    tinkernet::{
+        self,
+        runtime_types::sp_runtime::{DispatchError, ModuleError},
+    },
+};
+use subxt::{blocks::ExtrinsicEvents, Metadata, PolkadotConfig};
+
+/// Resolves a dispatch error carried by an event into `Pallet::Error (docs)` through the
+/// runtime metadata, falling back to the raw error for anything that is not a module error.
+pub fn describe(metadata: &Metadata, error: &DispatchError) -> String {
+    let DispatchError::Module(ModuleError { index, error }) = error else {
+        return format!("{:?}", error);
+    };
+
+    metadata
+        .pallet_by_index(*index)
+        .and_then(|pallet| {
+            pallet
+                .error_variant_by_index(error[0])
+                .map(|variant| module_error(pallet.name(), &variant.name, &variant.docs))
+        })
+        .unwrap_or_else(|| format!("unknown error #{} of pallet #{}", error[0], index))
+}
+
+/// Maps the error of a failed extrinsic, resolving module errors to their pallet and
+/// variant names and keeping every other error as the source.
+pub fn dispatch_failure(error: subxt::Error) -> ApiError {
+    let subxt::Error::Runtime(subxt::error::DispatchError::Module(module)) = &error else {
+        return ApiError::TransactionNotSuccessful(error);
+    };
+
+    let description = module.details().ok().map(|details| {
+        module_error(
+            details.pallet.name(),
+            &details.variant.name,
+            &details.variant.docs,
+        )
+    });
+
+    match description {
+        Some(description) => ApiError::DispatchFailed {
+            description,
+            source: error,
+        },
+        None => ApiError::TransactionNotSuccessful(error),
+    }
+}
+
+/// `utility.batch` and `INV4::operate_multisig` succeed even when the calls they dispatch
+/// fail, reporting the failure in their events instead; this turns those into errors.
+pub fn ensure_dispatched(
+    metadata: &Metadata,
+    events: &ExtrinsicEvents<PolkadotConfig>,
+) -> Result<(), CliError> {
+    if let Some(event) = events
+        .find_first::<tinkernet::utility::events::BatchInterrupted>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+    {
+        return Err(ApiError::BatchInterrupted {
+            index: event.index,
+            error: describe(metadata, &event.error),
+        }
+        .into());
+    }
+
+    if let Some(event) = events
+        .find_first::<tinkernet::inv4::events::MultisigExecuted>()
+        .map_err(|error| ApiError::DecodeFailed(error.into()))?
+    {
+        if let Err(error) = &event.result {
+            return Err(ApiError::MultisigCallFailed {
+                call_hash: hex::encode(event.call_hash.as_bytes()),
+                error: describe(metadata, error),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+fn module_error(pallet: &str, variant: &str, docs: &[String]) -> String {
+    let docs = docs.join(" ");
+
+    if docs.trim().is_empty() {
+        format!("{}::{}", pallet, variant)
+    } else {
+        format!("{}::{} ({})", pallet, variant, docs.trim())
+    }
+}
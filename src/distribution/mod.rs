@@ -0,0 +1,238 @@
+pub mod policy;
+
+use crate::{balance::Balance, error::DistributionError};
+use serde::{Serialize, Serializer};
+use std::{collections::BTreeMap, fmt};
+
+/// Fractional digits kept for reputation, enough for percentage bonuses on any base rep.
+const REP_DECIMALS: u32 = 6;
+const REP_ONE: u128 = 10u128.pow(REP_DECIMALS);
+
+/// Reputation as a fixed-point integer with `REP_DECIMALS` fractional digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Reputation(u128);
+
+impl Reputation {
+    pub const ZERO: Self = Self(0);
+
+    pub fn from_units(units: u32) -> Self {
+        Self(units as u128 * REP_ONE)
+    }
+
+    /// Scales the reputation by `numerator / denominator`, rounding down.
+    pub fn scale(self, numerator: u128, denominator: u128) -> Result<Self, DistributionError> {
+        Ok(Self(mul_div(self.0, numerator, denominator)?.0))
+    }
+
+    pub fn raw(self) -> u128 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, DistributionError> {
+        self.0
+            .checked_add(other.0)
+            .map(Self)
+            .ok_or(DistributionError::Overflow)
+    }
+
+    pub fn checked_mul(self, factor: u128) -> Result<Self, DistributionError> {
+        self.0
+            .checked_mul(factor)
+            .map(Self)
+            .ok_or(DistributionError::Overflow)
+    }
+
+    pub fn checked_sum(
+        reputations: impl IntoIterator<Item = Self>,
+    ) -> Result<Self, DistributionError> {
+        reputations
+            .into_iter()
+            .try_fold(Self::ZERO, Self::checked_add)
+    }
+}
+
+/// Adds `weight` to the entry of `key`, failing instead of overflowing.
+pub fn add_weight<K: Ord>(
+    weights: &mut BTreeMap<K, Reputation>,
+    key: K,
+    weight: Reputation,
+) -> Result<(), DistributionError> {
+    let entry = weights.entry(key).or_default();
+    *entry = entry.checked_add(weight)?;
+
+    Ok(())
+}
+
+impl fmt::Display for Reputation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Balance::new(self.0, REP_DECIMALS).fmt(f)
+    }
+}
+
+impl Serialize for Reputation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Splits `budget` between `weights` proportionally using the largest remainder method,
+/// so the returned shares always add up to exactly `budget` (or zero when every weight is zero).
+///
+/// Leftover planck go one each to the largest remainders, ties broken by key order,
+/// which keeps the result deterministic for the same input.
+pub fn allocate<K: Ord + Clone>(
+    budget: u128,
+    weights: &BTreeMap<K, Reputation>,
+) -> Result<BTreeMap<K, u128>, DistributionError> {
+    let total = weights
+        .values()
+        .try_fold(0u128, |total, weight| total.checked_add(weight.raw()))
+        .ok_or(DistributionError::Overflow)?;
+
+    if total == 0 {
+        return Ok(weights.keys().map(|key| (key.clone(), 0)).collect());
+    }
+
+    let mut shares: BTreeMap<K, u128> = BTreeMap::new();
+    let mut remainders: Vec<(u128, K)> = Vec::with_capacity(weights.len());
+
+    for (key, weight) in weights {
+        let (share, remainder) = mul_div(budget, weight.raw(), total)?;
+
+        shares.insert(key.clone(), share);
+        remainders.push((remainder, key.clone()));
+    }
+
+    let mut leftover = budget
+        .checked_sub(shares.values().sum::<u128>())
+        .ok_or(DistributionError::OverAllocated)?;
+
+    // Stable sort keeps key order among equal remainders.
+    remainders.sort_by(|a, b| b.0.cmp(&a.0));
+
+    for (_, key) in remainders {
+        if leftover == 0 {
+            break;
+        }
+
+        if let Some(share) = shares.get_mut(&key) {
+            *share += 1;
+            leftover -= 1;
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Computes `value * numerator / denominator` as a quotient and remainder, failing instead
+/// of wrapping when an intermediate product or the result does not fit in a `u128`.
+pub fn mul_div(
+    value: u128,
+    numerator: u128,
+    denominator: u128,
+) -> Result<(u128, u128), DistributionError> {
+    let (quotient, remainder) = (value / denominator, value % denominator);
+
+    let partial = remainder
+        .checked_mul(numerator)
+        .ok_or(DistributionError::Overflow)?;
+
+    let share = quotient
+        .checked_mul(numerator)
+        .and_then(|share| share.checked_add(partial / denominator))
+        .ok_or(DistributionError::Overflow)?;
+
+    Ok((share, partial % denominator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(units: &[(&'static str, u32)]) -> BTreeMap<&'static str, Reputation> {
+        units
+            .iter()
+            .map(|(key, units)| (*key, Reputation::from_units(*units)))
+            .collect()
+    }
+
+    #[test]
+    fn allocate_adds_up_to_budget() {
+        let sets = [
+            weights(&[("a", 1), ("b", 2), ("c", 3)]),
+            weights(&[("a", 7), ("b", 11), ("c", 13), ("d", 17)]),
+            weights(&[("a", 1)]),
+            weights(&[("a", 1_000_000), ("b", 1)]),
+        ];
+
+        for set in &sets {
+            for budget in [0, 1, 99, 100, 1_000_000_000_007] {
+                let shares = allocate(budget, set).unwrap();
+
+                assert_eq!(shares.values().sum::<u128>(), budget);
+                assert_eq!(shares.len(), set.len());
+            }
+        }
+    }
+
+    #[test]
+    fn allocate_gives_leftover_to_largest_remainders() {
+        let shares = allocate(100, &weights(&[("a", 1), ("b", 2), ("c", 3)])).unwrap();
+
+        assert_eq!(shares, BTreeMap::from([("a", 17), ("b", 33), ("c", 50)]));
+    }
+
+    #[test]
+    fn allocate_skips_zero_weight() {
+        let shares = allocate(10, &weights(&[("a", 0), ("b", 1), ("c", 1)])).unwrap();
+
+        assert_eq!(shares, BTreeMap::from([("a", 0), ("b", 5), ("c", 5)]));
+    }
+
+    #[test]
+    fn allocate_all_weights_zero() {
+        let shares = allocate(10, &weights(&[("a", 0), ("b", 0)])).unwrap();
+
+        assert_eq!(shares, BTreeMap::from([("a", 0), ("b", 0)]));
+    }
+
+    #[test]
+    fn allocate_breaks_ties_by_key_order() {
+        let shares = allocate(2, &weights(&[("c", 1), ("a", 1), ("b", 1)])).unwrap();
+
+        assert_eq!(shares, BTreeMap::from([("a", 1), ("b", 1), ("c", 0)]));
+    }
+
+    #[test]
+    fn allocate_budget_smaller_than_holder_count() {
+        let shares = allocate(
+            3,
+            &weights(&[("a", 1), ("b", 1), ("c", 1), ("d", 1), ("e", 1)]),
+        )
+        .unwrap();
+
+        assert_eq!(shares.values().sum::<u128>(), 3);
+        assert_eq!(
+            shares,
+            BTreeMap::from([("a", 1), ("b", 1), ("c", 1), ("d", 0), ("e", 0)])
+        );
+    }
+
+    #[test]
+    fn mul_div_rounds_down_with_remainder() {
+        assert_eq!(mul_div(10, 1, 3).unwrap(), (3, 1));
+        assert_eq!(mul_div(u128::MAX, 1, 2).unwrap(), (u128::MAX / 2, 1));
+    }
+
+    #[test]
+    fn mul_div_fails_on_overflow() {
+        assert!(matches!(
+            mul_div(2, u128::MAX, 3),
+            Err(DistributionError::Overflow)
+        ));
+        assert!(matches!(
+            mul_div(u128::MAX, 2, 1),
+            Err(DistributionError::Overflow)
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    distribution::{mul_div, Reputation},
+    error::{DistributionError, PolicyError},
+};
+use serde::{Deserialize, Serialize};
+
+/// Rules for turning NFT holdings into reputation and splitting a DAO's staking rewards.
+///
+/// `Policy::default()` reproduces the YoudleDAO rules; other DAOs can provide their own
+/// as a JSON file through `--policy`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    pub base_reputation: BaseReputation,
+
+    /// Item collections fetched from the indexer, which the rules below refer to by kind.
+    #[serde(default)]
+    pub items: Vec<ItemCollection>,
+
+    /// Items that replace the reputation of the NFT they are equipped to.
+    #[serde(default)]
+    pub upgrades: Vec<Upgrade>,
+
+    /// Items that multiply core and/or staker reputation when their name matches.
+    #[serde(default)]
+    pub multipliers: Vec<Multiplier>,
+
+    /// Items that add a percentage bonus for every one held by the owner.
+    #[serde(default)]
+    pub bonuses: Vec<Bonus>,
+
+    pub split: Split,
+}
+
+/// Metadata property holding an NFT's base reputation, and the value used when it is missing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BaseReputation {
+    pub property: String,
+    pub default: u32,
+}
+
+/// Items of one kind, selected on the indexer by collection and name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ItemCollection {
+    pub kind: ItemKind,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub collection_id: Option<String>,
+    /// Regular expression item names have to match, all items when not given.
+    #[serde(default)]
+    pub name_pattern: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Upgrade {
+    pub item: ItemKind,
+    pub from: Vec<u32>,
+    pub to: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Multiplier {
+    pub item: ItemKind,
+    /// Item names the multiplier applies to.
+    pub names: Vec<String>,
+    #[serde(default = "no_multiplier")]
+    pub core: u32,
+    #[serde(default = "no_multiplier")]
+    pub staker: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Bonus {
+    pub item: ItemKind,
+    pub percent_per_item: u32,
+}
+
+/// Percentages of the unclaimed core and staker rewards paid out to holders,
+/// and of both combined that is staked back into the core.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Split {
+    pub core_percent: u32,
+    pub staker_percent: u32,
+    pub restake_percent: u32,
+}
+
+/// Name of an item collection, as given by `ItemCollection::kind`.
+pub type ItemKind = String;
+
+fn no_multiplier() -> u32 {
+    1
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            base_reputation: BaseReputation {
+                property: String::from("Base Reputation"),
+                default: 1,
+            },
+            items: vec![
+                ItemCollection {
+                    kind: String::from("eyes"),
+                    symbol: Some(String::from("YOUDLEEYES")),
+                    collection_id: None,
+                    name_pattern: Some(String::from("^Youdle Eyes #0020$")),
+                },
+                ItemCollection {
+                    kind: String::from("backgrounds"),
+                    symbol: Some(String::from("YOUDLEBACKGROUND")),
+                    collection_id: None,
+                    name_pattern: Some(String::from("^Youdle Background #00(19|18)$")),
+                },
+                ItemCollection {
+                    kind: String::from("banners"),
+                    symbol: None,
+                    collection_id: Some(String::from("36af143c6012f6266b-YOUDLE_BRAND")),
+                    name_pattern: Some(String::from("^YoudleDAO Bannooooor$")),
+                },
+            ],
+            upgrades: vec![
+                Upgrade {
+                    item: String::from("eyes"),
+                    from: vec![1, 50],
+                    to: 100,
+                },
+                Upgrade {
+                    item: String::from("eyes"),
+                    from: vec![100],
+                    to: 150,
+                },
+            ],
+            multipliers: vec![
+                Multiplier {
+                    item: String::from("backgrounds"),
+                    names: vec![String::from("Youdle Background #0019")],
+                    core: 2,
+                    staker: 1,
+                },
+                Multiplier {
+                    item: String::from("backgrounds"),
+                    names: vec![String::from("Youdle Background #0018")],
+                    core: 1,
+                    staker: 2,
+                },
+            ],
+            bonuses: vec![Bonus {
+                item: String::from("banners"),
+                percent_per_item: 5,
+            }],
+            // 38% restaked so the payout never uses up the whole balance.
+            split: Split {
+                core_percent: 60,
+                staker_percent: 60,
+                restake_percent: 38,
+            },
+        }
+    }
+}
+
+impl Policy {
+    pub fn load(path: &str) -> Result<Self, PolicyError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| PolicyError::Read {
+            path: path.to_string(),
+            source,
+        })?;
+
+        let policy: Self =
+            serde_json::from_str(&contents).map_err(|source| PolicyError::Parse {
+                path: path.to_string(),
+                source,
+            })?;
+
+        policy.validate()?;
+
+        Ok(policy)
+    }
+
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        let invalid = |reason: String| Err(PolicyError::Invalid(reason));
+
+        if self.base_reputation.property.trim().is_empty() {
+            return invalid(String::from("base_reputation.property must not be empty"));
+        }
+
+        for (index, collection) in self.items.iter().enumerate() {
+            if collection.kind.trim().is_empty()
+                || (collection.symbol.is_none() && collection.collection_id.is_none())
+            {
+                return invalid(format!(
+                    "items[{}] needs a `kind` and a `symbol` or `collection_id`",
+                    index
+                ));
+            }
+
+            if self.items[..index]
+                .iter()
+                .any(|earlier| earlier.kind == collection.kind)
+            {
+                return invalid(format!(
+                    "items[{}] repeats the kind `{}`",
+                    index, collection.kind
+                ));
+            }
+        }
+
+        let rule_items = self
+            .upgrades
+            .iter()
+            .map(|upgrade| &upgrade.item)
+            .chain(self.multipliers.iter().map(|multiplier| &multiplier.item))
+            .chain(self.bonuses.iter().map(|bonus| &bonus.item));
+
+        for item in rule_items {
+            if !self.items.iter().any(|collection| &collection.kind == item) {
+                return invalid(format!("no item collection of kind `{}`", item));
+            }
+        }
+
+        for (index, upgrade) in self.upgrades.iter().enumerate() {
+            if upgrade.from.is_empty() || upgrade.to == 0 {
+                return invalid(format!(
+                    "upgrades[{}] needs at least one `from` value and a non-zero `to`",
+                    index
+                ));
+            }
+
+            let overlapping = self.upgrades[..index].iter().any(|earlier| {
+                earlier.item == upgrade.item
+                    && earlier.from.iter().any(|r| upgrade.from.contains(r))
+            });
+
+            if overlapping {
+                return invalid(format!(
+                    "upgrades[{}] matches a reputation already upgraded by an earlier rule",
+                    index
+                ));
+            }
+        }
+
+        for (index, multiplier) in self.multipliers.iter().enumerate() {
+            if multiplier.names.is_empty() || multiplier.core == 0 || multiplier.staker == 0 {
+                return invalid(format!(
+                    "multipliers[{}] needs `names` and non-zero multipliers",
+                    index
+                ));
+            }
+        }
+
+        for (index, bonus) in self.bonuses.iter().enumerate() {
+            if bonus.percent_per_item == 0 {
+                return invalid(format!("bonuses[{}] has a zero percentage", index));
+            }
+        }
+
+        let split = &self.split;
+
+        let within_budget = |percent: u32| {
+            percent
+                .checked_add(split.restake_percent)
+                .is_some_and(|total| total <= 100)
+        };
+
+        if !within_budget(split.core_percent) || !within_budget(split.staker_percent) {
+            return invalid(String::from(
+                "split pays out more than 100% of the unclaimed rewards once restaking is included",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// New reputation for an NFT with `current` reputation equipped with an item of `kind`,
+    /// `None` when no upgrade rule applies.
+    pub fn upgrade(&self, kind: &str, current: Reputation) -> Option<Reputation> {
+        self.upgrades
+            .iter()
+            .filter(|upgrade| upgrade.item == kind)
+            .find(|upgrade| {
+                upgrade
+                    .from
+                    .iter()
+                    .any(|from| Reputation::from_units(*from) == current)
+            })
+            .map(|upgrade| Reputation::from_units(upgrade.to))
+    }
+
+    pub fn has_upgrades(&self, kind: &str) -> bool {
+        self.upgrades.iter().any(|upgrade| upgrade.item == kind)
+    }
+
+    /// Core and staker multipliers for an item, combining every rule that matches its name.
+    pub fn multipliers(&self, kind: &str, name: &str) -> Result<(u128, u128), DistributionError> {
+        self.multipliers
+            .iter()
+            .filter(|multiplier| {
+                multiplier.item == kind && multiplier.names.iter().any(|n| n == name)
+            })
+            .try_fold((1u128, 1u128), |(core, staker), multiplier| {
+                Some((
+                    core.checked_mul(multiplier.core as u128)?,
+                    staker.checked_mul(multiplier.staker as u128)?,
+                ))
+            })
+            .ok_or(DistributionError::Overflow)
+    }
+
+    pub fn bonus_percent(&self, kind: &str) -> u128 {
+        self.bonuses
+            .iter()
+            .filter(|bonus| bonus.item == kind)
+            .map(|bonus| bonus.percent_per_item as u128)
+            .sum()
+    }
+
+    /// Splits unclaimed rewards into the (core, staker, restake) budgets.
+    pub fn budgets(
+        &self,
+        unclaimed_core: u128,
+        unclaimed_staker: u128,
+    ) -> Result<(u128, u128, u128), DistributionError> {
+        let unclaimed = unclaimed_core
+            .checked_add(unclaimed_staker)
+            .ok_or(DistributionError::Overflow)?;
+
+        Ok((
+            mul_div(unclaimed_core, self.split.core_percent as u128, 100)?.0,
+            mul_div(unclaimed_staker, self.split.staker_percent as u128, 100)?.0,
+            mul_div(unclaimed, self.split.restake_percent as u128, 100)?.0,
+        ))
+    }
+}
@@ -8,23 +8,227 @@ pub enum CliError {
     #[error("Error claiming staking rewards.")]
     Claim(#[from] ClaimError),
 
+    #[error("Error transferring funds.")]
+    Transfer(#[from] TransferError),
+
+    #[error("Error fetching account balances.")]
+    Balance(#[from] BalanceError),
+
+    #[error("Error resolving asset.")]
+    Asset(#[from] AssetError),
+
+    #[error("Error sending cross-chain message.")]
+    Xcm(#[from] XcmError),
+
     #[error("Error distributing YoudleDAO staking rewards.")]
     YoudleDist(#[from] YoudleDistError),
 
+    #[error("Error distributing DAO staking rewards.")]
+    Dao(#[from] DaoError),
+
+    #[error("Error querying NFTs.")]
+    Nft(#[from] NftError),
+
+    #[error("Runtime metadata error.")]
+    Metadata(#[from] MetadataError),
+
+    #[error("Error loading distribution policy.")]
+    Policy(#[from] PolicyError),
+
+    #[error("Error computing the distribution.")]
+    Distribution(#[from] DistributionError),
+
+    #[error("Indexer query failed.")]
+    Graphql(#[from] GraphqlError),
+
     #[error("Keystore error.")]
     Keystore(#[from] KeystoreError),
 
     #[error("Api Error.")]
     Api(#[from] ApiError),
 
-    #[error("Unknown error encountered.")]
-    Unknown,
+    #[error("Confirmation rejected.")]
+    Rejected,
+
+    #[error("--at can only be used with read-only commands and dry runs.")]
+    HistoricalSubmission,
+
+    #[error("Invalid tip: {0}")]
+    InvalidTip(String),
+
+    #[error("Failed to encode JSON output.")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Interactive prompt failed.")]
+    Prompt(#[from] inquire::InquireError),
+
+    #[error("Failed to read the keystore password.")]
+    PasswordInput(#[source] std::io::Error),
 }
 
 #[derive(Error, Debug)]
 pub enum YoudleDistError {
     #[error("Failed to decode an account during reward generation.")]
     FailedDecodingAccount,
+
+    #[error("Could not read snapshot file {path}.")]
+    SnapshotRead {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Could not parse snapshot file {path}.")]
+    SnapshotParse {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Could not encode the snapshot.")]
+    SnapshotEncode(#[source] serde_json::Error),
+
+    #[error("Could not write snapshot file {path}.")]
+    SnapshotWrite {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Found {0} anomalies in the NFT data, aborting because of --strict.")]
+    Anomalies(usize),
+
+    #[error("Invalid call hash: {0}")]
+    InvalidCallHash(String),
+
+    #[error("No pending YoudleDAO proposal with call hash {0}.")]
+    ProposalNotFound(String),
+
+    #[error("Proposal differs from the recomputed distribution in {0} places.")]
+    ProposalMismatch(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum DaoError {
+    #[error("Core #{0} does not exist.")]
+    CoreNotFound(u32),
+
+    #[error("Invalid account address: {0}")]
+    InvalidAccount(String),
+
+    #[error("Holder source needs {0}.")]
+    MissingHolderInput(&'static str),
+
+    #[error("Invalid Uniques collection id: {0}")]
+    InvalidCollection(String),
+
+    #[error("Could not read holders file {path}.")]
+    HoldersRead {
+        path: String,
+        #[source]
+        source: csv::Error,
+    },
+
+    #[error("Could not parse holders file {path}.")]
+    HoldersParse {
+        path: String,
+        #[source]
+        source: csv::Error,
+    },
+
+    #[error("The holder source returned no holders.")]
+    NoHolders,
+
+    #[error("--phase distribute needs --claim-block.")]
+    MissingClaimBlock,
+
+    #[error("A distribution of the rewards claimed in block {claim_block} is already pending as proposal {call_hash}.")]
+    DistributionPending {
+        claim_block: String,
+        call_hash: String,
+    },
+
+    #[error(
+        "The rewards claimed in block {claim_block} were already distributed in block #{block}."
+    )]
+    AlreadyDistributed { claim_block: String, block: u32 },
+
+    #[error("Invalid reserve amount: {0}")]
+    InvalidReserve(String),
+
+    #[error("No reward claims of the DAO found in block {0}.")]
+    NoClaimsFound(String),
+
+    #[error("The reserve is larger than the DAO's free balance of {0}.")]
+    ReserveExceedsBalance(String),
+
+    #[error("Could not write report {path}.")]
+    ReportWrite {
+        path: String,
+        #[source]
+        source: csv::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum DistributionError {
+    #[error("Arithmetic overflow while splitting the budget.")]
+    Overflow,
+
+    #[error("Rounded shares add up to more than the budget.")]
+    OverAllocated,
+}
+
+#[derive(Error, Debug)]
+pub enum NftError {
+    #[error("Item {0} has a non-numeric weight attribute: {1}")]
+    InvalidWeight(u32, String),
+
+    #[error("Total weight of holder {0} does not fit in a u32.")]
+    WeightOverflow(String),
+
+    #[error("Could not write {path}.")]
+    Write {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum MetadataError {
+    #[error(
+        "The node's runtime metadata differs from the one this CLI was built with ({0}). \
+         Run `metadata update` and rebuild, or pass --allow-outdated-metadata."
+    )]
+    Outdated(String),
+
+    #[error("Could not write {path}.")]
+    Write {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Could not read policy file {path}.")]
+    Read {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Could not parse policy file {path}.")]
+    Parse {
+        path: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid policy: {0}")]
+    Invalid(String),
 }
 
 #[derive(Error, Debug)]
@@ -36,28 +240,80 @@ pub enum KeystoreError {
     KeyNotFound,
 }
 
+#[derive(Error, Debug)]
+pub enum GraphqlError {
+    #[error("Request to the indexer failed: {0}")]
+    Request(String),
+
+    #[error("Indexer responded with HTTP status {0}.")]
+    Status(u16),
+
+    #[error("Could not decode the indexer response: {0}")]
+    Decode(String),
+
+    #[error("Indexer returned errors: {0}")]
+    Query(String),
+
+    #[error("Indexer returned no data.")]
+    EmptyResponse,
+}
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("Failed to connect to the provided endpoint.")]
-    EndpointConnectionFailed,
+    EndpointConnectionFailed(#[source] subxt::Error),
 
     #[error("Failed to get chain storage data.")]
-    StorageFailed,
+    StorageFailed(#[source] subxt::Error),
 
     #[error("Failed to decode storage data.")]
-    DecodeFailed,
+    DecodeFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 
     #[error("Failed to sign transaction payload.")]
-    SigningFailed,
+    SigningFailed(#[source] subxt::Error),
 
     #[error("Failed submitting transaction to RPC.")]
-    SubmissionFailed,
+    SubmissionFailed(#[source] subxt::Error),
 
-    #[error("Transaction was not successful: {0}")]
+    #[error("Transaction was not successful.")]
     TransactionNotSuccessful(#[from] subxt::Error),
 
+    #[error("Transaction failed with {description}")]
+    DispatchFailed {
+        description: String,
+        #[source]
+        source: subxt::Error,
+    },
+
+    #[error("Batch interrupted at call #{index} with {error}")]
+    BatchInterrupted { index: u32, error: String },
+
+    #[error("Multisig call {call_hash} executed but failed with {error}")]
+    MultisigCallFailed { call_hash: String, error: String },
+
     #[error("Could not find the relevant events resulting from the transaction")]
     EventNotFound,
+
+    #[error("Block {0} not found.")]
+    BlockNotFound(String),
+
+    #[error("Transaction {extrinsic_hash} dropped: {message}")]
+    TransactionDropped {
+        extrinsic_hash: String,
+        message: String,
+    },
+
+    #[error(
+        "Transaction {0} was dropped, but its nonce has been used since. It may have been \
+         included anyway, check it before submitting again."
+    )]
+    NonceConsumed(String),
+
+    #[error("Transaction rejected by the pool: {0}")]
+    TransactionInvalid(String),
+
+    #[error("Failed to fetch the account nonce.")]
+    NonceFailed(#[source] subxt::Error),
 }
 
 #[derive(Error, Debug)]
@@ -68,6 +324,74 @@ pub enum InsertKeyError {
 
 #[derive(Error, Debug)]
 pub enum ClaimError {
-    #[error("Confirmation rejected.")]
-    Rejected,
+    #[error("Staker rewards are claimed oldest first, start the range at or before era {0}.")]
+    StakerEraSkipped(u32),
+
+    #[error("No claimable rewards in the given era range.")]
+    NothingToClaim,
+
+    #[error("Pass --all or an era range with --start and/or --end.")]
+    InvalidEraRange,
+
+    #[error("Pass --core, optionally with --as-staker, or --staker.")]
+    InvalidTarget,
+}
+
+#[derive(Error, Debug)]
+pub enum TransferError {
+    #[error("Invalid destination address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Failed to read transfers from the CSV file.")]
+    CsvRead(#[source] csv::Error),
+
+    #[error("The CSV file does not contain any transfers.")]
+    EmptyBatch,
+
+    #[error("Core #{0} does not exist.")]
+    CoreNotFound(u32),
+
+    #[error("Transfer to {address} would leave it below the existential deposit of {minimum}.")]
+    BelowExistentialDeposit { address: String, minimum: String },
+
+    #[error("Sending account does not have enough free balance to cover the transfers and stay above the existential deposit.")]
+    InsufficientBalance,
+}
+
+#[derive(Error, Debug)]
+pub enum BalanceError {
+    #[error("Invalid account address: {0}")]
+    InvalidAddress(String),
+}
+
+#[derive(Error, Debug)]
+pub enum AssetError {
+    #[error("No registered asset matches \"{0}\".")]
+    UnknownAsset(String),
+}
+
+#[derive(Error, Debug)]
+pub enum XcmError {
+    #[error("Invalid destination account: {0}")]
+    InvalidDestination(String),
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("Asset {0} has no XCM v3 location in the asset registry.")]
+    UnsupportedAssetLocation(String),
+
+    #[error("Asset \"{asset}\" is not known on {chain}.")]
+    UnknownChainAsset { chain: String, asset: String },
+
+    #[error("The call must be hex encoded.")]
+    InvalidCall,
+
+    #[error(
+        "Decimals of asset \"{0}\" are unknown, pass them or give the amount as planck:<amount>."
+    )]
+    UnknownDecimals(String),
 }
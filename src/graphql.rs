@@ -0,0 +1,143 @@
+use crate::error::GraphqlError;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Rows requested per page by `GraphqlClient::query_all`.
+pub const PAGE_SIZE: usize = 500;
+
+/// Minimal client for the GraphQL indexers (RMRK, OCIF squid) the CLI reads from.
+#[derive(Debug, Clone)]
+pub struct GraphqlClient {
+    url: String,
+    retries: u32,
+    backoff: Duration,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Request<'a> {
+    operation_name: &'a str,
+    query: &'a str,
+    variables: Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct Response<Data> {
+    data: Option<Data>,
+    errors: Option<Vec<ErrorMessage>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorMessage {
+    message: String,
+}
+
+impl GraphqlClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+
+    /// Runs a query, retrying connection failures and server errors with exponential backoff.
+    /// Errors reported by the GraphQL server itself are returned without retrying.
+    pub async fn query<Variables: Serialize, Data: DeserializeOwned>(
+        &self,
+        operation_name: &str,
+        query: &str,
+        variables: &Variables,
+    ) -> Result<Data, GraphqlError> {
+        let request = Request {
+            operation_name,
+            query,
+            variables: serde_json::to_value(variables)
+                .map_err(|e| GraphqlError::Decode(e.to_string()))?,
+        };
+
+        let mut attempt = 0;
+
+        loop {
+            match self.send(&request).await {
+                Err(error @ (GraphqlError::Request(_) | GraphqlError::Status(_)))
+                    if attempt < self.retries =>
+                {
+                    let delay = self.backoff * 2u32.pow(attempt);
+
+                    eprintln!(
+                        "GraphQL request to {} failed ({}), retrying in {:?}.",
+                        self.url, error, delay
+                    );
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Runs a query taking `$limit` and `$offset` variables page by page until an empty page
+    /// comes back, collecting the rows `rows` extracts from each page. A short page doesn't end
+    /// the query, as indexers may cap pages below `PAGE_SIZE`.
+    pub async fn query_all<Data: DeserializeOwned, Row>(
+        &self,
+        operation_name: &str,
+        query: &str,
+        variables: Map<String, Value>,
+        rows: impl Fn(Data) -> Vec<Row>,
+    ) -> Result<Vec<Row>, GraphqlError> {
+        let mut all = Vec::new();
+
+        loop {
+            let mut page_variables = variables.clone();
+            page_variables.insert(String::from("limit"), PAGE_SIZE.into());
+            page_variables.insert(String::from("offset"), all.len().into());
+
+            let page = rows(self.query(operation_name, query, &page_variables).await?);
+
+            if page.is_empty() {
+                return Ok(all);
+            }
+
+            all.extend(page);
+        }
+    }
+
+    async fn send<Data: DeserializeOwned>(
+        &self,
+        request: &Request<'_>,
+    ) -> Result<Data, GraphqlError> {
+        let mut response = surf::post(&self.url)
+            .body_json(request)
+            .map_err(|e| GraphqlError::Request(e.to_string()))?
+            .await
+            .map_err(|e| GraphqlError::Request(e.to_string()))?;
+
+        if response.status().is_server_error() {
+            return Err(GraphqlError::Status(response.status().into()));
+        }
+
+        let body: Response<Data> = response
+            .body_json()
+            .await
+            .map_err(|e| GraphqlError::Decode(e.to_string()))?;
+
+        if let Some(errors) = body.errors.filter(|errors| !errors.is_empty()) {
+            return Err(GraphqlError::Query(
+                errors
+                    .into_iter()
+                    .map(|error| error.message)
+                    .collect::<Vec<String>>()
+                    .join("; "),
+            ));
+        }
+
+        body.data.ok_or(GraphqlError::EmptyResponse)
+    }
+}
@@ -1,13 +1,25 @@
 use clap::Parser;
 
+mod balance;
 mod commands;
+mod dispatch;
+mod distribution;
 mod error;
+mod graphql;
 mod keystore;
-mod util;
+mod staking;
+mod uniques;
 use anyhow::Result;
 use commands::{
-    claim_command, insert_key_command, youdle_staking_distribution_command, Args, Commands,
-    ExtraArgs, StakingCommands, YoudlesCommands,
+    assets_list_command, balance_command, chain_info_command, claim_command,
+    dao_distribute_rewards_command, insert_key_command, metadata_update_command,
+    nft_holders_command, rings_send_call_command, rings_transfer_assets_command,
+    staking_rewards_command,
+    submit::{NonceManager, SubmitOptions},
+    transfer_batch_command, transfer_command, xcm_transfer_command, youdle_default_policy_command,
+    youdle_snapshot_command, youdle_staking_distribution_command, youdle_verify_command, Args,
+    AssetsCommands, ChainCommands, Commands, DaoCommands, ExtraArgs, MetadataCommands, NftCommands,
+    RingsCommands, StakingCommands, XcmCommands, YoudlesCommands,
 };
 
 #[subxt::subxt(runtime_metadata_path = "./metadata.scale")]
@@ -19,14 +31,180 @@ async fn main() -> Result<()> {
 
     let extra = ExtraArgs {
         endpoint: args.endpoint,
+        rmrk_graphql: args.rmrk_graphql,
+        ocif_squid: args.ocif_squid,
+        at: args.at,
+        submit: SubmitOptions {
+            wait: args.wait,
+            explorer: args.explorer,
+            retries: args.retries,
+            nonce: NonceManager::new(args.nonce),
+            tip: args.tip,
+            mortality: args.mortality,
+            fee_asset: args.fee_asset,
+            allow_outdated_metadata: args.allow_outdated_metadata,
+        },
     };
 
     match args.command {
         Commands::InsertKey { name, key } => insert_key_command(name, key)?,
+        Commands::Transfer {
+            account,
+            to,
+            amount,
+            asset,
+            core,
+        } => transfer_command(account, to, amount, asset, core, extra).await?,
+        Commands::TransferBatch {
+            account,
+            csv,
+            asset,
+            core,
+        } => transfer_batch_command(account, csv, asset, core, extra).await?,
+        Commands::Balance {
+            accounts,
+            asset,
+            json,
+        } => balance_command(accounts, asset, json, extra).await?,
+        Commands::Assets(assets_command) => match assets_command {
+            AssetsCommands::List { json } => assets_list_command(json, extra).await?,
+        },
+        Commands::Xcm(xcm_command) => match xcm_command {
+            XcmCommands::Transfer {
+                account,
+                asset,
+                to_chain,
+                dest,
+                amount,
+                weight_limit,
+                weight_proof_size,
+                core,
+            } => {
+                xcm_transfer_command(
+                    account,
+                    asset,
+                    to_chain,
+                    dest,
+                    amount,
+                    weight_limit,
+                    weight_proof_size,
+                    core,
+                    extra,
+                )
+                .await?
+            }
+        },
+        Commands::Rings(rings_command) => match rings_command {
+            RingsCommands::SendCall {
+                account,
+                core,
+                chain,
+                call,
+                weight_ref_time,
+                weight_proof_size,
+                xcm_fee_asset,
+                fee,
+                fee_decimals,
+            } => {
+                rings_send_call_command(
+                    account,
+                    core,
+                    chain,
+                    call,
+                    weight_ref_time,
+                    weight_proof_size,
+                    xcm_fee_asset,
+                    fee,
+                    fee_decimals,
+                    extra,
+                )
+                .await?
+            }
+            RingsCommands::TransferAssets {
+                account,
+                core,
+                chain,
+                asset,
+                amount,
+                decimals,
+                to,
+                xcm_fee_asset,
+                fee,
+                fee_decimals,
+            } => {
+                rings_transfer_assets_command(
+                    account,
+                    core,
+                    chain,
+                    asset,
+                    amount,
+                    decimals,
+                    to,
+                    xcm_fee_asset,
+                    fee,
+                    fee_decimals,
+                    extra,
+                )
+                .await?
+            }
+        },
         Commands::Youdles(youdles_command) => match youdles_command {
-            YoudlesCommands::DistributeRewards { account, csv } => {
-                youdle_staking_distribution_command(account, csv, extra).await?
+            YoudlesCommands::DistributeRewards {
+                account,
+                nfts_csv,
+                payouts_csv,
+                policy,
+                from_snapshot,
+                dry_run,
+                strict,
+                report,
+                cross_check,
+                phase,
+                claim_block,
+                reserve,
+            } => {
+                youdle_staking_distribution_command(
+                    account,
+                    nfts_csv,
+                    payouts_csv,
+                    policy,
+                    from_snapshot,
+                    dry_run,
+                    strict,
+                    report,
+                    cross_check,
+                    phase,
+                    claim_block,
+                    reserve,
+                    extra,
+                )
+                .await?
             }
+            YoudlesCommands::Snapshot {
+                output,
+                policy,
+                cross_check,
+            } => youdle_snapshot_command(output, policy, cross_check, extra).await?,
+            YoudlesCommands::Verify {
+                call_hash,
+                policy,
+                from_snapshot,
+                cross_check,
+                claim_block,
+                reserve,
+            } => {
+                youdle_verify_command(
+                    call_hash,
+                    policy,
+                    from_snapshot,
+                    cross_check,
+                    claim_block,
+                    reserve,
+                    extra,
+                )
+                .await?
+            }
+            YoudlesCommands::DefaultPolicy => youdle_default_policy_command()?,
         },
         Commands::Staking(staking_command) => match staking_command {
             StakingCommands::Claim {
@@ -38,6 +216,58 @@ async fn main() -> Result<()> {
                 start,
                 end,
             } => claim_command(account, core, staker, as_staker, all, start, end, extra).await?,
+            StakingCommands::Rewards { core, staker, json } => {
+                staking_rewards_command(core, staker, json, extra).await?
+            }
+        },
+
+        Commands::Nft(subcommand) => match subcommand {
+            NftCommands::Holders {
+                collection,
+                weight_attribute,
+                json,
+                output,
+            } => nft_holders_command(collection, weight_attribute, json, output, extra).await?,
+        },
+
+        Commands::Metadata(subcommand) => match subcommand {
+            MetadataCommands::Update { output } => metadata_update_command(output, extra).await?,
+        },
+
+        Commands::Chain(subcommand) => match subcommand {
+            ChainCommands::Info { json } => chain_info_command(json, extra).await?,
+        },
+
+        Commands::Dao(subcommand) => match subcommand {
+            DaoCommands::DistributeRewards {
+                account,
+                core,
+                source,
+                collection,
+                holders,
+                policy,
+                payouts_csv,
+                dry_run,
+                phase,
+                claim_block,
+                reserve,
+            } => {
+                dao_distribute_rewards_command(
+                    account,
+                    core,
+                    source,
+                    collection,
+                    holders,
+                    policy,
+                    payouts_csv,
+                    dry_run,
+                    phase,
+                    claim_block,
+                    reserve,
+                    extra,
+                )
+                .await?
+            }
         },
     };
 
@@ -0,0 +1,249 @@
+use crate::{
+    error::{ApiError, CliError},
+    tinkernet::{self, runtime_types::pallet_ocif_staking::primitives::CoreStakeInfo},
+};
+use serde::{Deserialize, Serialize};
+use subxt::{dynamic::Value, storage::Storage, OnlineClient, PolkadotConfig};
+
+const PERBILL: u128 = 1_000_000_000;
+
+/// Rewards claimable for a single era.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraReward {
+    pub era: u32,
+    pub amount: u128,
+}
+
+/// Unclaimed core rewards of `core_id`, one entry per era that `core_claim_rewards` would
+/// accept, using the same formula as `pallet_ocif_staking`.
+pub async fn core_rewards(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    core_id: u32,
+) -> Result<Vec<EraReward>, CliError> {
+    let current_era = current_era(storage).await?;
+
+    let keys: Vec<Value> = vec![core_id.into()];
+    let core_storage_query = subxt::dynamic::storage("OcifStaking", "CoreEraStake", keys);
+
+    let mut results = storage
+        .iter(core_storage_query)
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let mut rewards = Vec::new();
+
+    while let Some(Ok(kv)) = results.next().await {
+        let stake = kv
+            .value
+            .as_type::<CoreStakeInfo<u128>>()
+            .map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        let Some(era) = kv.keys[1].as_u128().map(|k| k as u32) else {
+            continue;
+        };
+
+        if stake.reward_claimed || stake.total == 0 || era >= current_era {
+            continue;
+        }
+
+        let Some(era_info) = storage
+            .fetch(&tinkernet::storage().ocif_staking().general_era_info(era))
+            .await
+            .map_err(ApiError::StorageFailed)?
+        else {
+            continue;
+        };
+
+        let amount = if stake.active {
+            perbill_mul(
+                perbill_from_rational(stake.total, era_info.active_stake),
+                era_info.rewards.core,
+            )
+        } else {
+            0
+        };
+
+        rewards.push(EraReward { era, amount });
+    }
+
+    rewards.sort_by_key(|reward| reward.era);
+
+    Ok(rewards)
+}
+
+/// Staker rewards `account` can claim from `core_id`, in the order successive
+/// `staker_claim_rewards` calls pay them out.
+pub async fn staker_rewards(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    core_id: u32,
+    account: subxt::utils::AccountId32,
+) -> Result<Vec<EraReward>, CliError> {
+    let current_era = current_era(storage).await?;
+
+    let stakes: Vec<(u32, u128)> = storage
+        .fetch_or_default(
+            &tinkernet::storage()
+                .ocif_staking()
+                .general_staker_info(core_id, account),
+        )
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .stakes
+        .iter()
+        .map(|stake| (stake.era, stake.staked))
+        .collect();
+
+    let mut rewards = Vec::new();
+
+    for (era, staked) in staker_claims(stakes, current_era) {
+        // The pallet fails the claim when the era has no reward info, so later eras
+        // can't be claimed either.
+        let Some(era_info) = storage
+            .fetch(&tinkernet::storage().ocif_staking().general_era_info(era))
+            .await
+            .map_err(ApiError::StorageFailed)?
+        else {
+            break;
+        };
+
+        let core_total = storage
+            .fetch(
+                &tinkernet::storage()
+                    .ocif_staking()
+                    .core_era_stake(core_id, era),
+            )
+            .await
+            .map_err(ApiError::StorageFailed)?
+            .map(|stake| stake.total)
+            .unwrap_or(0);
+
+        let stakers_joint_reward = perbill_mul(
+            perbill_from_rational(core_total, era_info.staked),
+            era_info.rewards.stakers,
+        );
+
+        rewards.push(EraReward {
+            era,
+            amount: perbill_mul(
+                perbill_from_rational(staked, core_total),
+                stakers_joint_reward,
+            ),
+        });
+    }
+
+    Ok(rewards)
+}
+
+/// Replays `StakerInfo::claim` over a staker's `(era, staked)` history and returns the
+/// `(era, staked)` pairs each successive `staker_claim_rewards` call would claim before
+/// hitting the current era or an empty stake.
+pub fn staker_claims(mut stakes: Vec<(u32, u128)>, current_era: u32) -> Vec<(u32, u128)> {
+    let mut claims = Vec::new();
+
+    while let Some(&(era, staked)) = stakes.first() {
+        if stakes.len() == 1 || stakes[1].0 > era + 1 {
+            stakes[0] = (era + 1, staked);
+        } else {
+            stakes.remove(0);
+        }
+
+        if stakes.first().is_some_and(|(_, staked)| *staked == 0) {
+            stakes.remove(0);
+        }
+
+        if staked == 0 || era >= current_era {
+            break;
+        }
+
+        claims.push((era, staked));
+    }
+
+    claims
+}
+
+/// Ids of all cores registered for staking.
+pub async fn registered_cores(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<Vec<u32>, CliError> {
+    let mut results = storage
+        .iter(subxt::dynamic::storage(
+            "OcifStaking",
+            "RegisteredCore",
+            Vec::<Value>::new(),
+        ))
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let mut cores = Vec::new();
+
+    while let Some(Ok(kv)) = results.next().await {
+        if let Some(core_id) = kv.keys[0].as_u128() {
+            cores.push(core_id as u32);
+        }
+    }
+
+    cores.sort();
+
+    Ok(cores)
+}
+
+pub async fn current_era(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+) -> Result<u32, CliError> {
+    storage
+        .fetch_or_default(&tinkernet::storage().ocif_staking().current_era())
+        .await
+        .map_err(|error| ApiError::StorageFailed(error).into())
+}
+
+/// `Perbill::from_rational`: rounds down and saturates at 100% (also for a zero denominator).
+fn perbill_from_rational(numerator: u128, denominator: u128) -> u128 {
+    if denominator == 0 || numerator >= denominator {
+        PERBILL
+    } else {
+        numerator.saturating_mul(PERBILL) / denominator
+    }
+}
+
+/// `Perbill * Balance`: rounds to the nearest value, preferring down on a tie.
+fn perbill_mul(parts: u128, value: u128) -> u128 {
+    let whole = value / PERBILL * parts;
+    let rest = value % PERBILL * parts;
+
+    whole + rest / PERBILL + u128::from(rest % PERBILL * 2 > PERBILL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staker_claims_single_stake_until_current_era() {
+        assert_eq!(
+            staker_claims(vec![(5, 100)], 8),
+            vec![(5, 100), (6, 100), (7, 100)]
+        );
+    }
+
+    #[test]
+    fn staker_claims_follows_stake_changes() {
+        assert_eq!(
+            staker_claims(vec![(5, 100), (6, 200)], 8),
+            vec![(5, 100), (6, 200), (7, 200)]
+        );
+    }
+
+    #[test]
+    fn staker_claims_stops_after_unstaking() {
+        assert_eq!(
+            staker_claims(vec![(5, 100), (7, 0)], 10),
+            vec![(5, 100), (6, 100)]
+        );
+    }
+
+    #[test]
+    fn staker_claims_nothing_before_current_era() {
+        assert!(staker_claims(vec![(8, 100)], 8).is_empty());
+        assert!(staker_claims(Vec::new(), 8).is_empty());
+    }
+}
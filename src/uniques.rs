@@ -0,0 +1,68 @@
+use crate::{
+    error::{ApiError, CliError},
+    tinkernet::{
+        self,
+        runtime_types::{
+            bounded_collections::bounded_vec::BoundedVec, pallet_uniques::types::ItemDetails,
+        },
+    },
+};
+use subxt::{dynamic::Value, storage::Storage, OnlineClient, PolkadotConfig};
+
+/// An item of a `Uniques` collection and its current owner.
+#[derive(Debug, Clone)]
+pub struct CollectionItem {
+    pub item: u32,
+    pub owner: subxt::utils::AccountId32,
+}
+
+/// Every item of `collection`, ordered by item id.
+pub async fn collection_items(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    collection: u32,
+) -> Result<Vec<CollectionItem>, CliError> {
+    let keys: Vec<Value> = vec![collection.into()];
+
+    let mut results = storage
+        .iter(subxt::dynamic::storage("Uniques", "Asset", keys))
+        .await
+        .map_err(ApiError::StorageFailed)?;
+
+    let mut items = Vec::new();
+
+    while let Some(Ok(kv)) = results.next().await {
+        let details = kv
+            .value
+            .as_type::<ItemDetails<subxt::utils::AccountId32, u128>>()
+            .map_err(|error| ApiError::DecodeFailed(error.into()))?;
+
+        if let Some(item) = kv.keys[1].as_u128() {
+            items.push(CollectionItem {
+                item: item as u32,
+                owner: details.owner,
+            });
+        }
+    }
+
+    items.sort_by_key(|item| item.item);
+
+    Ok(items)
+}
+
+/// Value of the `key` attribute set on an item, if any.
+pub async fn item_attribute(
+    storage: &Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    collection: u32,
+    item: u32,
+    key: &str,
+) -> Result<Option<Vec<u8>>, CliError> {
+    Ok(storage
+        .fetch(&tinkernet::storage().uniques().attribute(
+            collection,
+            Some(item),
+            BoundedVec(key.as_bytes().to_vec()),
+        ))
+        .await
+        .map_err(ApiError::StorageFailed)?
+        .map(|(value, _deposit)| value.0))
+}